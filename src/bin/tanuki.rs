@@ -0,0 +1,255 @@
+use std::io;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Parser, ValueEnum};
+use serde::Serialize;
+use walkdir::WalkDir;
+
+use tanuki::keyword::{KeywordCategory, KeywordPriority};
+use tanuki::{KeywordManager, ParsedFilename, ParsedPath};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    Text, // Human-readable, one block per file
+    Json, // A single array
+    Jsonl, // One object per line
+}
+
+///
+/// A filename from the arguments or stdin, or a file found in a directory, with its parent folders.
+///
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum ParseResult {
+    Filename(ParsedFilename),
+    Path(ParsedPath),
+}
+
+///
+/// Parses video filenames, e.g. "[SubsPlease] Jujutsu Kaisen Season 2 - 01 [1080p].mkv"
+///
+#[derive(Debug, Parser)]
+#[command(name = "tanuki", version)]
+struct Cli {
+    /// Filenames to parse. Reads one filename per line from stdin if neither filenames nor a directory are given.
+    filenames: Vec<String>,
+
+    /// Also parses the video files of a directory and its subdirectories, using their parent folders.
+    #[arg(short, long, value_name = "DIR")]
+    dir: Option<PathBuf>,
+
+    /// Additional keywords, from a ".toml" or ".json" file.
+    #[arg(short, long, value_name = "FILE")]
+    keywords: Option<PathBuf>,
+
+    #[arg(short, long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let mut keyword_manager = KeywordManager::new();
+    if let Some(path) = &cli.keywords {
+        if let Err(e) = keyword_manager.load_file(path) {
+            eprintln!("tanuki: {}: {}", path.display(), e);
+            return ExitCode::FAILURE;
+        }
+    }
+
+    let results = match get_results(&cli, &keyword_manager) {
+        Ok(results) => results,
+        Err(e) => {
+            eprintln!("tanuki: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match print_results(&results, cli.format) {
+        Ok(()) => ExitCode::SUCCESS,
+        // e.g. piped into "head"
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("tanuki: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+///
+/// Parses the filenames followed by the files of the directory, e.g. "tanuki a.mkv --dir /anime".
+///
+fn get_results(cli: &Cli, keyword_manager: &KeywordManager) -> io::Result<Vec<ParseResult>> {
+    let filenames = if cli.filenames.is_empty() && cli.dir.is_none() {
+        io::stdin()
+            .lock()
+            .lines()
+            .filter(|line| !line.as_ref().is_ok_and(|l| l.trim().is_empty()))
+            .collect::<io::Result<Vec<String>>>()?
+    } else {
+        cli.filenames.clone()
+    };
+
+    let mut results: Vec<ParseResult> = filenames
+        .iter()
+        .map(|filename| ParseResult::Filename(tanuki::parse_with_keyword_manager(filename, keyword_manager)))
+        .collect();
+    if let Some(dir) = &cli.dir {
+        for path in walk_dir(dir, keyword_manager)? {
+            results.push(ParseResult::Path(ParsedPath::parse(path, keyword_manager)));
+        }
+    }
+    Ok(results)
+}
+
+///
+/// Collects the video files, e.g. "mkv", so that "cover.jpg" is skipped.
+/// Subtitles, audio and archives are low priority file extensions, e.g. "ass" or "zip", and are skipped too.
+///
+fn walk_dir(dir: &Path, keyword_manager: &KeywordManager) -> io::Result<Vec<PathBuf>> {
+    let mut paths = vec![];
+    for entry in WalkDir::new(dir).sort_by_file_name() {
+        let entry = entry?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let is_video = entry
+            .path()
+            .extension()
+            .and_then(|e| e.to_str())
+            .and_then(|e| keyword_manager.find_by_category(e, KeywordCategory::FileExtension))
+            .is_some_and(|k| k.priority == KeywordPriority::Normal);
+        if is_video {
+            paths.push(entry.into_path());
+        }
+    }
+    Ok(paths)
+}
+
+fn print_results(results: &[ParseResult], format: Format) -> io::Result<()> {
+    let mut stdout = io::stdout().lock();
+    match format {
+        Format::Text => {
+            for (i, result) in results.iter().enumerate() {
+                if i > 0 {
+                    writeln!(stdout)?;
+                }
+                let text = match result {
+                    ParseResult::Filename(parsed) => format_text(&parsed.file_name, parsed),
+                    ParseResult::Path(parsed_path) => format_text(&parsed_path.path, &parsed_path.parsed),
+                };
+                write!(stdout, "{}", text)?;
+            }
+        }
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut stdout, results)?;
+            writeln!(stdout)?;
+        }
+        Format::Jsonl => {
+            for result in results {
+                serde_json::to_writer(&mut stdout, result)?;
+                writeln!(stdout)?;
+            }
+        }
+    }
+    stdout.flush()
+}
+
+///
+/// e.g.
+/// [SubsPlease] Jujutsu Kaisen - 01 [1080p].mkv
+///   title:            Jujutsu Kaisen
+///   episode_numbers:  01
+///
+fn format_text(name: &str, parsed: &ParsedFilename) -> String {
+    let episode_range = parsed.episode_range.as_ref().map(|r| format!("{}-{}", r.start.text, r.end.text));
+    let fields: Vec<(&str, Vec<String>)> = vec![
+        ("title", parsed.title.iter().cloned().collect()),
+        ("episode_title", parsed.episode_title.iter().cloned().collect()),
+        ("release_group", parsed.release_group.iter().cloned().collect()),
+        ("seasons", parsed.seasons.clone()),
+        ("episode_numbers", parsed.episode_numbers.iter().map(|e| e.text.clone()).collect()),
+        ("episode_numbers_alt", parsed.episode_numbers_alt.iter().map(|e| e.text.clone()).collect()),
+        ("episode_range", episode_range.into_iter().collect()),
+        ("parts", parsed.parts.clone()),
+        ("volume_numbers", parsed.volume_numbers.clone()),
+        ("years", parsed.years.clone()),
+        ("release_version", parsed.release_version.iter().map(|v| v.to_string()).collect()),
+        ("video_resolution", parsed.video_resolution.iter().map(|r| r.to_string()).collect()),
+        ("file_checksum", parsed.file_checksum.iter().cloned().collect()),
+        ("file_extension", parsed.file_extension.iter().cloned().collect()),
+        ("anime_types", parsed.anime_types.clone()),
+        ("audio_terms", parsed.audio_terms.clone()),
+        ("video_terms", parsed.video_terms.clone()),
+        ("sources", parsed.sources.clone()),
+        ("languages", parsed.languages.clone()),
+        ("subtitles", parsed.subtitles.clone()),
+        ("device_compatibility", parsed.device_compatibility.clone()),
+        ("release_information", parsed.release_information.clone()),
+    ];
+
+    let mut text = format!("{}\n", name);
+    for (name, values) in fields.iter().filter(|(_, values)| !values.is_empty()) {
+        text.push_str(format!("  {:<22}{}\n", format!("{}:", name), values.join(", ")).as_str());
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory;
+
+    use super::*;
+
+    #[test]
+    fn test_format_text() {
+        let parsed = tanuki::parse("[SubsPlease] Jujutsu Kaisen - 01v2 [1080p].mkv");
+        assert_eq!(
+            "[SubsPlease] Jujutsu Kaisen - 01v2 [1080p].mkv\n\
+             \x20 title:                Jujutsu Kaisen\n\
+             \x20 release_group:        SubsPlease\n\
+             \x20 episode_numbers:      01\n\
+             \x20 release_version:      2\n\
+             \x20 video_resolution:     1080p\n\
+             \x20 file_extension:       mkv\n",
+            format_text(&parsed.file_name, &parsed)
+        );
+    }
+
+    #[test]
+    fn test_cli() {
+        Cli::command().debug_assert();
+
+        let cli = Cli::try_parse_from(["tanuki", "--format", "jsonl", "a.mkv", "b.mkv"]).unwrap();
+        assert_eq!(Format::Jsonl, cli.format);
+        assert_eq!(vec!["a.mkv", "b.mkv"], cli.filenames);
+        assert!(Cli::try_parse_from(["tanuki", "--format", "xml"]).is_err());
+
+        let cli = Cli::try_parse_from(["tanuki", "a.mkv", "--dir", "/anime"]).unwrap();
+        assert_eq!(vec!["a.mkv"], cli.filenames);
+        assert_eq!(Some(PathBuf::from("/anime")), cli.dir);
+    }
+
+    #[test]
+    fn test_walk_dir() {
+        let dir = std::env::temp_dir().join(format!("tanuki-cli-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("Season 1")).unwrap();
+        for name in ["Season 1/Title - 02 [1080p].mkv", "Title - 01 [1080p].mkv", "Title - 01 [1080p].ass", "Title - 01.zip", "cover.jpg"] {
+            std::fs::write(dir.join(name), "").unwrap();
+        }
+
+        let paths = walk_dir(&dir, &KeywordManager::new()).unwrap();
+        assert_eq!(vec![dir.join("Season 1/Title - 02 [1080p].mkv"), dir.join("Title - 01 [1080p].mkv")], paths);
+
+        // Filenames and the files of the directory are parsed together
+        let cli = Cli::try_parse_from(["tanuki", "[Group] Other - 03.mkv", "--dir", dir.to_str().unwrap()]).unwrap();
+        let results = get_results(&cli, &KeywordManager::new()).unwrap();
+        assert_eq!(3, results.len());
+        assert!(matches!(&results[0], ParseResult::Filename(p) if p.release_group.as_deref() == Some("Group")));
+        assert!(matches!(&results[1], ParseResult::Path(p) if p.parsed.seasons == vec!["1"]));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,97 @@
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::path::Path;
+
+use crc32fast::Hasher;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ChecksumStatus {
+    Match,
+    Mismatch { expected: u32, actual: u32 },
+    NoChecksum, // The file name doesn't contain a checksum, e.g. "[0F5F884F]"
+}
+
+///
+/// Verifies the file contents against the CRC32 checksum found in its name.
+/// e.g. "[ST]_Kemono_no_Souja_Erin_-_12_(1280x720_h264)_[0F5F884F].mkv"
+///
+pub fn verify_checksum<P: AsRef<Path>>(path: P) -> io::Result<ChecksumStatus> {
+    let path = path.as_ref();
+    let file_name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid file name"))?;
+
+    let expected = match crate::parse(file_name).file_checksum.and_then(|c| u32::from_str_radix(c.as_str(), 16).ok()) {
+        Some(expected) => expected,
+        None => return Ok(ChecksumStatus::NoChecksum),
+    };
+
+    let actual = compute_crc32(File::open(path)?)?;
+
+    if actual == expected {
+        Ok(ChecksumStatus::Match)
+    } else {
+        Ok(ChecksumStatus::Mismatch { expected, actual })
+    }
+}
+
+///
+/// Computes the CRC32 checksum of the reader in chunks, so large files are never fully loaded.
+///
+pub fn compute_crc32<R: Read>(mut reader: R) -> io::Result<u32> {
+    let mut hasher = Hasher::new();
+    let mut buffer = vec![0; CHUNK_SIZE];
+
+    loop {
+        let read = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        hasher.update(&buffer[..read]);
+    }
+
+    Ok(hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn test_compute_crc32() {
+        assert_eq!(0xCBF43926, compute_crc32("123456789".as_bytes()).unwrap());
+        assert_eq!(0, compute_crc32("".as_bytes()).unwrap());
+    }
+
+    #[test]
+    fn test_verify_checksum() {
+        let dir = std::env::temp_dir().join(format!("tanuki-checksum-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let matching = dir.join("[Group] Title - 01 [CBF43926].mkv");
+        let mismatching = dir.join("[Group] Title - 01 [0F5F884F].mkv");
+        let no_checksum = dir.join("[Group] Title - 01 [1080p].mkv");
+        for path in [&matching, &mismatching, &no_checksum] {
+            fs::write(path, "123456789").unwrap();
+        }
+
+        assert_eq!(ChecksumStatus::Match, verify_checksum(&matching).unwrap());
+        assert_eq!(ChecksumStatus::Mismatch { expected: 0x0F5F884F, actual: 0xCBF43926 }, verify_checksum(&mismatching).unwrap());
+        assert_eq!(ChecksumStatus::NoChecksum, verify_checksum(&no_checksum).unwrap());
+        assert!(verify_checksum(dir.join("[Group] Title - 02 [CBF43926].mkv")).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,77 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+///
+/// The parser rule that decided a value, from the most to the least reliable.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Rule {
+    CombinedSeasonEpisode, // e.g. "S01E04", "01x04"
+    ResolutionFormat, // e.g. "1080p", "1280x720"
+    Keyword, // e.g. "BDRip", "x265"
+    Prefix, // e.g. "Season 2", "S02", "Vol.3", "E05"
+    OrdinalPrefix, // e.g. "2nd Season"
+    EpisodeVersion, // e.g. "04v2"
+    Checksum, // e.g. "[0F5F884F]"
+    PrefixRange, // e.g. "Seasons 1-4", "S1-S3", "Volumes 01-04"
+    LeadingBrackets, // e.g. "[SubsPlease] Title"
+    EnclosedYear, // e.g. "(2019)"
+    EpisodeRange, // e.g. "(01-12)", "E01-E03"
+    MultiEpisode, // e.g. "01+02"
+    NumberAfterSeparator, // e.g. "Title - 04 [1080p]"
+    DecimalEpisode, // e.g. "Title - 07.5"
+    LeadingWords, // e.g. the title in "[Group] Title - 04"
+    SeasonEpisodePadding, // e.g. "Season 1 - 03", only the episode is zero padded
+    EnclosedNumberAfterEpisode, // e.g. "S02E01 (26)"
+    SceneSuffix, // e.g. "...x265-EMBER"
+    YearBeforeMetadata, // e.g. "Show.2019.S01E01"
+    SeasonEpisodeDelimiter, // e.g. "Season 01 - 03"
+    NumberBeforeMetadata, // e.g. "Title_04_[720p]"
+    NumberOnly, // e.g. "05.mkv"
+    WordsAfterEpisode, // e.g. the episode title in "Title - 04 - The Labyrinth"
+    LowPriorityKeyword, // e.g. "END", "NCOP"
+    SeasonRangeUndelimited, // e.g. "Season 01-03"
+    SeasonEpisodeLargeNumber, // e.g. "Season 1 - 12", the number is too large to be a season
+    SeasonRangeSmallNumbers, // e.g. "Season 1 - 5"
+}
+
+impl Rule {
+    ///
+    /// How likely a value decided by the rule is correct, from 0.0 to 1.0.
+    ///
+    pub fn score(&self) -> f32 {
+        match self {
+            Rule::CombinedSeasonEpisode | Rule::ResolutionFormat => 1.0,
+            Rule::Keyword | Rule::Prefix | Rule::OrdinalPrefix | Rule::EpisodeVersion => 0.95,
+            Rule::Checksum | Rule::PrefixRange | Rule::LeadingBrackets | Rule::EnclosedYear => 0.9,
+            Rule::EpisodeRange | Rule::MultiEpisode => 0.85,
+            Rule::NumberAfterSeparator | Rule::DecimalEpisode | Rule::LeadingWords => 0.8,
+            Rule::SeasonEpisodePadding | Rule::EnclosedNumberAfterEpisode | Rule::SceneSuffix | Rule::YearBeforeMetadata => 0.7,
+            Rule::SeasonEpisodeDelimiter | Rule::NumberBeforeMetadata | Rule::NumberOnly | Rule::WordsAfterEpisode | Rule::LowPriorityKeyword => 0.6,
+            Rule::SeasonRangeUndelimited => 0.5,
+            Rule::SeasonEpisodeLargeNumber | Rule::SeasonRangeSmallNumbers => 0.4,
+        }
+    }
+}
+
+///
+/// Confidence of an extracted field, e.g. the episode number of "Title - 04 [1080p]" is
+/// 0.8 by the NumberAfterSeparator rule.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FieldConfidence {
+    pub score: f32,
+    pub rule: Rule,
+}
+
+impl From<Rule> for FieldConfidence {
+    fn from(rule: Rule) -> Self {
+        FieldConfidence {
+            score: rule.score(),
+            rule,
+        }
+    }
+}
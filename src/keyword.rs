@@ -1,8 +1,13 @@
 #![allow(dead_code)]
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 use crate::token::TokenKind;
 
 #[derive(Debug, Eq, PartialEq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum KeywordCategory {
     SeasonPrefix,
     EpisodePrefix,
@@ -22,12 +27,16 @@ pub enum KeywordCategory {
 }
 
 #[derive(Debug, Eq, PartialEq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum KeywordPriority {
     Normal, // Will override Unknown tokens
     Low // Low priority keywords will be parsed at the end (after other tokens have been analyzed)
 }
 
 #[derive(Debug, Eq, PartialEq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum KeywordKind {
     Standalone, // e.g. MOVIE
     Combined { next_token_kind: TokenKind }, // Needs to be followed by a specific token to be valid. e.g. S01 or E01
@@ -37,6 +46,7 @@ pub enum KeywordKind {
 }
 
 #[derive(Debug, Eq, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Keyword {
     pub value: String,
     pub category: KeywordCategory,
@@ -56,34 +66,19 @@ impl Keyword {
     }
 
     pub fn is_standalone(&self) -> bool {
-        match self.kind {
-            KeywordKind::Standalone => true,
-            _ => false
-        }
+        matches!(self.kind, KeywordKind::Standalone)
     }
     pub fn is_combined(&self) -> bool {
-        match self.kind {
-            KeywordKind::Combined { .. } => true,
-            _ => false
-        }
+        matches!(self.kind, KeywordKind::Combined { .. })
     }
     pub fn is_combined_or_separated(&self) -> bool {
-        match self.kind {
-            KeywordKind::CombinedOrSeparated { .. } => true,
-            _ => false
-        }
+        matches!(self.kind, KeywordKind::CombinedOrSeparated { .. })
     }
     pub fn is_separated(&self) -> bool {
-        match self.kind {
-            KeywordKind::Separated { .. } => true,
-            _ => false
-        }
+        matches!(self.kind, KeywordKind::Separated { .. })
     }
     pub fn is_ordinal_suffix(&self) -> bool {
-        match self.kind {
-            KeywordKind::OrdinalSuffix { .. } => true,
-            _ => false
-        }
+        matches!(self.kind, KeywordKind::OrdinalSuffix)
     }
 
 }
\ No newline at end of file
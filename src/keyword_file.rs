@@ -0,0 +1,204 @@
+use std::fmt::{Display, Formatter};
+use std::io;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+
+use crate::keyword::{KeywordCategory, KeywordKind, KeywordPriority};
+use crate::token::TokenKind;
+
+static CATEGORIES: [(&str, KeywordCategory); 15] = [
+    ("season_prefix", KeywordCategory::SeasonPrefix),
+    ("episode_prefix", KeywordCategory::EpisodePrefix),
+    ("volume_prefix", KeywordCategory::VolumePrefix),
+    ("part_prefix", KeywordCategory::PartPrefix),
+    ("anime_type", KeywordCategory::AnimeType),
+    ("video_term", KeywordCategory::VideoTerm),
+    ("audio_term", KeywordCategory::AudioTerm),
+    ("device_compat", KeywordCategory::DeviceCompat),
+    ("file_extension", KeywordCategory::FileExtension),
+    ("language", KeywordCategory::Language),
+    ("release_info", KeywordCategory::ReleaseInfo),
+    ("release_version", KeywordCategory::ReleaseVersion),
+    ("release_group", KeywordCategory::ReleaseGroup),
+    ("subtitles", KeywordCategory::Subtitles),
+    ("source", KeywordCategory::Source),
+];
+
+static KINDS: [(&str, KeywordKind); 5] = [
+    ("standalone", KeywordKind::Standalone),
+    ("combined", KeywordKind::Combined { next_token_kind: TokenKind::NumberLike }),
+    ("combined_or_separated", KeywordKind::CombinedOrSeparated { next_token_kind: TokenKind::NumberLike }),
+    ("separated", KeywordKind::Separated { next_token_kind: TokenKind::NumberLike }),
+    ("ordinal_suffix", KeywordKind::OrdinalSuffix),
+];
+
+static PRIORITIES: [(&str, KeywordPriority); 2] = [
+    ("normal", KeywordPriority::Normal),
+    ("low", KeywordPriority::Low),
+];
+
+///
+/// Keyword groups loaded from a TOML or JSON file, e.g.
+///
+/// ```toml
+/// [[groups]]
+/// category = "source"
+/// kind = "standalone" # default
+/// priority = "normal" # default
+/// values = ["AMZN", "CR"]
+/// ```
+///
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct KeywordFile {
+    #[serde(default)]
+    groups: Vec<KeywordFileGroup>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct KeywordFileGroup {
+    category: String,
+    kind: Option<String>,
+    priority: Option<String>,
+    values: Vec<String>,
+}
+
+pub(crate) struct KeywordGroup {
+    pub category: KeywordCategory,
+    pub kind: KeywordKind,
+    pub priority: KeywordPriority,
+    pub values: Vec<String>,
+}
+
+#[derive(Debug)]
+pub enum KeywordFileError {
+    Io(io::Error),
+    Toml(toml::de::Error),
+    Json(serde_json::Error),
+    UnsupportedFormat(PathBuf), // Only ".toml" and ".json" files are supported
+    UnknownCategory(String),
+    UnknownKind(String),
+    UnknownPriority(String),
+}
+
+impl KeywordFile {
+    pub(crate) fn from_toml(input: &str) -> Result<KeywordFile, KeywordFileError> {
+        toml::from_str(input).map_err(KeywordFileError::Toml)
+    }
+
+    pub(crate) fn from_json(input: &str) -> Result<KeywordFile, KeywordFileError> {
+        serde_json::from_str(input).map_err(KeywordFileError::Json)
+    }
+
+    ///
+    /// Resolves the names of every group, so that nothing is added if a single group is invalid.
+    ///
+    pub(crate) fn into_groups(self) -> Result<Vec<KeywordGroup>, KeywordFileError> {
+        self.groups
+            .into_iter()
+            .map(|group| {
+                Ok(KeywordGroup {
+                    category: find_by_name(&CATEGORIES, group.category.as_str())
+                        .ok_or(KeywordFileError::UnknownCategory(group.category))?,
+                    kind: match group.kind {
+                        Some(kind) => find_by_name(&KINDS, kind.as_str()).ok_or(KeywordFileError::UnknownKind(kind))?,
+                        None => KeywordKind::Standalone,
+                    },
+                    priority: match group.priority {
+                        Some(priority) => find_by_name(&PRIORITIES, priority.as_str()).ok_or(KeywordFileError::UnknownPriority(priority))?,
+                        None => KeywordPriority::Normal,
+                    },
+                    values: group.values,
+                })
+            })
+            .collect()
+    }
+}
+
+fn find_by_name<T: Copy>(names: &[(&str, T)], name: &str) -> Option<T> {
+    names.iter().find(|(n, _)| *n == name).map(|(_, value)| *value)
+}
+
+fn join_names<T>(names: &[(&str, T)]) -> String {
+    names.iter().map(|(n, _)| *n).collect::<Vec<&str>>().join(", ")
+}
+
+impl Display for KeywordFileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeywordFileError::Io(e) => write!(f, "could not read the keyword file: {}", e),
+            KeywordFileError::Toml(e) => write!(f, "invalid TOML keyword file: {}", e),
+            KeywordFileError::Json(e) => write!(f, "invalid JSON keyword file: {}", e),
+            KeywordFileError::UnsupportedFormat(path) => write!(f, "unsupported keyword file \"{}\", expected a .toml or .json file", path.display()),
+            KeywordFileError::UnknownCategory(name) => write!(f, "unknown keyword category \"{}\", expected one of: {}", name, join_names(&CATEGORIES)),
+            KeywordFileError::UnknownKind(name) => write!(f, "unknown keyword kind \"{}\", expected one of: {}", name, join_names(&KINDS)),
+            KeywordFileError::UnknownPriority(name) => write!(f, "unknown keyword priority \"{}\", expected one of: {}", name, join_names(&PRIORITIES)),
+        }
+    }
+}
+
+impl std::error::Error for KeywordFileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            KeywordFileError::Io(e) => Some(e),
+            KeywordFileError::Toml(e) => Some(e),
+            KeywordFileError::Json(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for KeywordFileError {
+    fn from(e: io::Error) -> Self {
+        KeywordFileError::Io(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_into_groups() {
+        let groups = KeywordFile::from_toml(r#"
+            [[groups]]
+            category = "source"
+            values = ["AMZN", "CR"]
+
+            [[groups]]
+            category = "episode_prefix"
+            kind = "combined_or_separated"
+            priority = "low"
+            values = ["EPI"]
+        "#).unwrap().into_groups().unwrap();
+
+        assert_eq!(2, groups.len());
+        assert_eq!(KeywordCategory::Source, groups[0].category);
+        assert_eq!(KeywordKind::Standalone, groups[0].kind);
+        assert_eq!(KeywordPriority::Normal, groups[0].priority);
+        assert_eq!(KeywordKind::CombinedOrSeparated { next_token_kind: TokenKind::NumberLike }, groups[1].kind);
+        assert_eq!(KeywordPriority::Low, groups[1].priority);
+    }
+
+    #[test]
+    fn test_unknown_names() {
+        let error = KeywordFile::from_json(r#"{"groups": [{"category": "streaming", "values": ["AMZN"]}]}"#)
+            .unwrap()
+            .into_groups()
+            .err()
+            .unwrap();
+        assert!(matches!(&error, KeywordFileError::UnknownCategory(name) if name == "streaming"));
+        assert!(error.to_string().starts_with("unknown keyword category \"streaming\", expected one of: season_prefix, "));
+
+        let error = KeywordFile::from_json(r#"{"groups": [{"category": "source", "kind": "prefix", "values": []}]}"#)
+            .unwrap()
+            .into_groups()
+            .err()
+            .unwrap();
+        assert!(matches!(error, KeywordFileError::UnknownKind(_)));
+
+        assert!(matches!(KeywordFile::from_toml("[[groups]]\nvalues = [\"AMZN\"]"), Err(KeywordFileError::Toml(_))));
+    }
+}
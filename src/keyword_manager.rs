@@ -1,18 +1,34 @@
 #![allow(dead_code)]
 
+#[cfg(feature = "keyword-file")]
+use std::fs;
+#[cfg(feature = "keyword-file")]
+use std::path::Path;
+use std::sync::{Arc, LazyLock};
+
 use crate::keyword::{Keyword, KeywordCategory, KeywordKind, KeywordPriority};
+#[cfg(feature = "keyword-file")]
+use crate::keyword_file::{KeywordFile, KeywordFileError};
 use crate::token::{TokenKind};
 
+static DEFAULT_KEYWORD_MANAGER: LazyLock<KeywordManager> = LazyLock::new(KeywordManager::build_default);
+
+///
+/// Keywords recognized by the parser.
+/// Clones share their keywords until one of them is changed, so a clone per parse is cheap.
+///
 #[derive(Debug, Clone)]
 pub struct KeywordManager {
-    keywords: Vec<(String, Keyword)>,
+    keywords: Arc<Vec<(String, Keyword)>>,
 }
 
 impl KeywordManager {
     pub fn new() -> KeywordManager {
-        let mut kwm = KeywordManager {
-            keywords: vec![]
-        };
+        DEFAULT_KEYWORD_MANAGER.clone()
+    }
+
+    fn build_default() -> KeywordManager {
+        let mut kwm = KeywordManager::empty();
 
         kwm
             // Seasons
@@ -243,7 +259,19 @@ impl KeywordManager {
         kwm
     }
 
-    fn add_group(
+    ///
+    /// A keyword manager without the default keywords, e.g. to replace them entirely.
+    ///
+    pub fn empty() -> KeywordManager {
+        KeywordManager {
+            keywords: Arc::new(vec![])
+        }
+    }
+
+    ///
+    /// Adds a keyword for each value, e.g. new sources or release groups.
+    ///
+    pub fn add_group(
         &mut self,
         keyword_category: KeywordCategory,
         keyword_kind: KeywordKind,
@@ -251,21 +279,88 @@ impl KeywordManager {
         values: Vec<&str>,
     ) -> &mut Self {
         values.iter().for_each(|value| {
-            self.keywords.push(
-                (
-                    value.to_string(),
-                    Keyword::new(value.to_string(), keyword_category, keyword_kind, keyword_priority)
-                ),
-            );
+            self.add(value, Keyword::new(value.to_string(), keyword_category, keyword_kind, keyword_priority));
         });
         self
     }
 
-    fn add(&mut self, value: &str, keyword: Keyword) -> &mut Self {
-        self.keywords.push((value.to_string(), keyword));
+    ///
+    /// Adds a keyword, overriding the one with the same value, category and kind (e.g. to change its priority).
+    /// Values are case-insensitive.
+    ///
+    pub fn add(&mut self, value: &str, mut keyword: Keyword) -> &mut Self {
+        let value = value.to_uppercase();
+        keyword.value = keyword.value.to_uppercase();
+
+        match Arc::make_mut(&mut self.keywords).iter_mut().find(|(key, val)| *key == value && val.category == keyword.category && val.kind == keyword.kind) {
+            Some(existing) => existing.1 = keyword,
+            None => Arc::make_mut(&mut self.keywords).push((value, keyword)),
+        }
+        self
+    }
+
+    ///
+    /// Removes every keyword with the value, whatever its category, e.g. "TS" is both a file extension and release information.
+    ///
+    pub fn remove(&mut self, value: &str) -> &mut Self {
+        let value = value.to_uppercase();
+        Arc::make_mut(&mut self.keywords).retain(|(key, _)| *key != value);
         self
     }
 
+    ///
+    /// Removes every keyword of the category.
+    ///
+    pub fn remove_category(&mut self, category: KeywordCategory) -> &mut Self {
+        self.remove_where(|keyword| keyword.category == category)
+    }
+
+    ///
+    /// Removes every keyword matching the predicate, e.g. by kind or priority.
+    /// e.g. `remove_where(|k| k.priority == KeywordPriority::Low)`
+    ///
+    pub fn remove_where<F: FnMut(&Keyword) -> bool>(&mut self, mut predicate: F) -> &mut Self {
+        Arc::make_mut(&mut self.keywords).retain(|(_, val)| !predicate(val));
+        self
+    }
+
+    pub fn keywords(&self) -> impl Iterator<Item = &Keyword> {
+        self.keywords.iter().map(|(_, keyword)| keyword)
+    }
+
+    ///
+    /// Adds the keyword groups of a ".toml" or ".json" file, overriding existing keywords.
+    /// Start from `KeywordManager::empty()` to replace the default keywords instead.
+    ///
+    #[cfg(feature = "keyword-file")]
+    pub fn load_file<P: AsRef<Path>>(&mut self, path: P) -> Result<&mut Self, KeywordFileError> {
+        let path = path.as_ref();
+        let input = fs::read_to_string(path)?;
+        match path.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase()).as_deref() {
+            Some("toml") => self.load_toml(input.as_str()),
+            Some("json") => self.load_json(input.as_str()),
+            _ => Err(KeywordFileError::UnsupportedFormat(path.to_path_buf())),
+        }
+    }
+
+    #[cfg(feature = "keyword-file")]
+    pub fn load_toml(&mut self, input: &str) -> Result<&mut Self, KeywordFileError> {
+        self.add_keyword_file(KeywordFile::from_toml(input)?)
+    }
+
+    #[cfg(feature = "keyword-file")]
+    pub fn load_json(&mut self, input: &str) -> Result<&mut Self, KeywordFileError> {
+        self.add_keyword_file(KeywordFile::from_json(input)?)
+    }
+
+    #[cfg(feature = "keyword-file")]
+    fn add_keyword_file(&mut self, keyword_file: KeywordFile) -> Result<&mut Self, KeywordFileError> {
+        for group in keyword_file.into_groups()? {
+            self.add_group(group.category, group.kind, group.priority, group.values.iter().map(String::as_str).collect());
+        }
+        Ok(self)
+    }
+
     //-----------
 
     /// Find a keyword by value.
@@ -277,6 +372,15 @@ impl KeywordManager {
             .map(|(_, value)| value.clone())
     }
 
+    /// Find a standalone keyword by value within a category.
+    pub fn find_by_category(&self, token_value: &str, category: KeywordCategory) -> Option<Keyword> {
+        self.keywords
+            .iter()
+            .filter(|(_, val)| val.is_standalone() && val.category == category)
+            .find(|(key, _)| key.to_uppercase() == token_value.to_uppercase())
+            .map(|(_, value)| value.clone())
+    }
+
     /// Find a keyword by value.
     pub fn find(&self, token_value: &str) -> Option<Keyword> {
         let keyword = self.find_standalone(token_value);
@@ -288,14 +392,11 @@ impl KeywordManager {
                 let combined_or_separated_keywords = self.get_combined_or_separated_keywords();
 
                 // Find the keyword with the longest key
-                match combined_or_separated_keywords
+                combined_or_separated_keywords
                     .iter()
                     .filter(|(key, _val)| uppercase_value.starts_with(key))
                     .max_by_key(|(key, _val)| key.len())
-                {
-                    Some((_, longest_keyword)) => Some(longest_keyword.clone()),
-                    None => None
-                }
+                    .map(|(_, longest_keyword)| longest_keyword.clone())
             }
         }
     }
@@ -303,13 +404,14 @@ impl KeywordManager {
     /// Find a keyword by value.
     pub fn find_many(&self, token_value: &str) -> Option<Vec<Keyword>> {
         // Get keywords that are Combined or CombinedOrSeparated
+        let token_value = token_value.to_uppercase();
         let filtered: Vec<Keyword> = self.keywords
-            .iter().cloned()
-            .filter(|(key, _val)| token_value.to_uppercase().starts_with(key))
-            .map(|(_, val)| val)
+            .iter()
+            .filter(|(key, _val)| token_value.starts_with(key))
+            .map(|(_, val)| val.clone())
             .collect();
 
-        return Some(filtered);
+        Some(filtered)
     }
 
 
@@ -317,20 +419,26 @@ impl KeywordManager {
 
     /// Get standalone keywords
     fn get_standalone_keywords(&self) -> Vec<(String, Keyword)> {
-        return self.keywords
-            .clone()
-            .into_iter()
+        self.keywords
+            .iter()
             .filter(|(_key, val)| { val.is_standalone() })
-            .collect();
+            .cloned()
+            .collect()
     }
 
     /// Get keywords that require to be combined or separated
     fn get_combined_or_separated_keywords(&self) -> Vec<(String, Keyword)> {
-        return self.keywords
-            .clone()
-            .into_iter()
+        self.keywords
+            .iter()
             .filter(|(_key, val)| { val.is_combined() || val.is_combined_or_separated() || val.is_ordinal_suffix() })
-            .collect();
+            .cloned()
+            .collect()
+    }
+}
+
+impl Default for KeywordManager {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -341,8 +449,6 @@ fn test_new_keyword_manager() {
     let keyword_manager = KeywordManager::new();
 
     println!("{:#?}", keyword_manager);
-
-    assert!(true);
 }
 
 #[test]
@@ -376,3 +482,85 @@ fn test_find_02() {
     assert_eq!(String::from("EP"), ret2.unwrap().value);
     assert_eq!(String::from("OP"), ret3.unwrap().value);
 }
+
+#[test]
+fn test_add_and_remove() {
+    let mut keyword_manager = KeywordManager::new();
+    keyword_manager
+        .add_group(KeywordCategory::Source, KeywordKind::Standalone, KeywordPriority::Normal, vec!["Amzn", "CR"])
+        .remove("TS");
+
+    assert_eq!(KeywordCategory::Source, keyword_manager.find("amzn").unwrap().category);
+    assert_eq!(String::from("AMZN"), keyword_manager.find("AMZN").unwrap().value);
+    assert_eq!(None, keyword_manager.find_standalone("TS"));
+
+    // Same value, category and kind overrides the default
+    let count = keyword_manager.keywords().count();
+    keyword_manager.add("END", Keyword::new(String::from("END"), KeywordCategory::ReleaseInfo, KeywordKind::Standalone, KeywordPriority::Normal));
+    assert_eq!(count, keyword_manager.keywords().count());
+    assert_eq!(KeywordPriority::Normal, keyword_manager.find("END").unwrap().priority);
+
+    keyword_manager.remove_category(KeywordCategory::Source);
+    assert_eq!(None, keyword_manager.find("AMZN"));
+    assert_eq!(0, KeywordManager::empty().keywords().count());
+}
+
+#[test]
+fn test_remove_where() {
+    let mut keyword_manager = KeywordManager::new();
+    keyword_manager.remove_where(|k| k.priority == KeywordPriority::Low);
+    assert!(keyword_manager.keywords().all(|k| k.priority != KeywordPriority::Low));
+    assert_eq!(None, keyword_manager.find_standalone("END"));
+    assert!(keyword_manager.find_standalone("BD").is_some());
+
+    keyword_manager.remove_where(|k| k.category == KeywordCategory::AnimeType && k.is_combined_or_separated());
+    assert!(keyword_manager.keywords().all(|k| k.category != KeywordCategory::AnimeType || !k.is_combined_or_separated()));
+    assert!(keyword_manager.keywords().any(|k| k.category == KeywordCategory::AnimeType));
+}
+
+#[test]
+fn test_shared_default() {
+    let default = KeywordManager::new();
+    let mut keyword_manager = KeywordManager::new();
+    assert!(Arc::ptr_eq(&default.keywords, &keyword_manager.keywords));
+
+    // Changing a clone doesn't change the default keywords
+    keyword_manager.remove("BD");
+    assert!(!Arc::ptr_eq(&default.keywords, &keyword_manager.keywords));
+    assert!(KeywordManager::new().find_standalone("BD").is_some());
+}
+
+#[cfg(feature = "keyword-file")]
+#[test]
+fn test_load_file() {
+    let dir = std::env::temp_dir().join(format!("tanuki-keywords-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let toml_path = dir.join("keywords.toml");
+    std::fs::write(&toml_path, "[[groups]]\ncategory = \"source\"\nvalues = [\"AMZN\"]\n").unwrap();
+    let json_path = dir.join("keywords.json");
+    std::fs::write(&json_path, r#"{"groups": [{"category": "release_group", "values": ["Judas"]}]}"#).unwrap();
+
+    let mut keyword_manager = KeywordManager::new();
+    keyword_manager.load_file(&toml_path).unwrap().load_file(&json_path).unwrap();
+    assert_eq!(KeywordCategory::Source, keyword_manager.find("AMZN").unwrap().category);
+    assert_eq!(KeywordCategory::ReleaseGroup, keyword_manager.find("JUDAS").unwrap().category);
+    assert!(keyword_manager.find("MOVIE").is_some());
+
+    // Replaces the default keywords
+    let mut keyword_manager = KeywordManager::empty();
+    keyword_manager.load_file(&toml_path).unwrap();
+    assert_eq!(1, keyword_manager.keywords().count());
+
+    // Nothing is added if a group is invalid
+    let mut keyword_manager = KeywordManager::empty();
+    let ret = keyword_manager.load_toml("[[groups]]\ncategory = \"source\"\nvalues = [\"AMZN\"]\n[[groups]]\ncategory = \"streaming\"\nvalues = [\"CR\"]\n");
+    assert!(matches!(ret, Err(KeywordFileError::UnknownCategory(_))));
+    assert_eq!(0, keyword_manager.keywords().count());
+
+    assert!(matches!(KeywordManager::empty().load_file(dir.join("keywords.txt")), Err(KeywordFileError::Io(_))));
+    std::fs::write(dir.join("keywords.txt"), "").unwrap();
+    assert!(matches!(KeywordManager::empty().load_file(dir.join("keywords.txt")), Err(KeywordFileError::UnsupportedFormat(_))));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -1,17 +1,70 @@
-mod tokenizer;
-mod keyword;
-mod token;
-mod token_manager;
-mod keyword_manager;
-mod metadata;
-mod parser;
+pub mod tokenizer;
+pub mod keyword;
+pub mod token;
+pub mod token_manager;
+pub mod keyword_manager;
+#[cfg(feature = "keyword-file")]
+pub mod keyword_file;
+pub mod metadata;
+pub mod parser;
+pub mod parsed_filename;
+pub mod parsed_path;
+pub mod video_resolution;
+pub mod checksum;
+pub mod confidence;
 mod token_helper;
 mod utils;
 
+use std::path::Path;
+
+use crate::parser::Parser;
+
+#[cfg(feature = "keyword-file")]
+pub use crate::keyword_file::KeywordFileError;
+pub use crate::keyword_manager::KeywordManager;
+pub use crate::parsed_filename::{EpisodeNumber, EpisodeRange, ParsedFilename};
+pub use crate::parsed_path::ParsedPath;
+pub use crate::video_resolution::VideoResolution;
+pub use crate::checksum::{verify_checksum, ChecksumStatus};
+pub use crate::confidence::{FieldConfidence, Rule};
+pub use crate::token::Span;
+
+///
+/// Parses a filename, e.g. "[HorribleSubs] Tower of Druaga - Sword of Uruk - 04 [480p].mkv"
+///
+pub fn parse(input: &str) -> ParsedFilename {
+    parse_with_keyword_manager(input, &KeywordManager::new())
+}
+
+///
+/// Parses a filename with custom keywords.
+/// e.g. to recognize additional sources, or a release group without brackets
+///
+pub fn parse_with_keyword_manager(input: &str, keyword_manager: &KeywordManager) -> ParsedFilename {
+    let (file_name, file_extension) = tokenizer::split_file_extension(input, keyword_manager);
+
+    // Spans are relative to the input, even if it is a path
+    let tokens = tokenizer::tokenize_at(&file_name, tokenizer::file_name_offset(input));
+
+    let mut parser = Parser::new_with_keyword_manager(tokens, keyword_manager.clone());
+    parser.parse();
+
+    ParsedFilename::from_tokens(input, file_extension, parser.token_manager.tokens())
+}
+
+///
+/// Parses a full path, using the parent folders for the fields missing from the file name.
+/// e.g. "/Anime/Jujutsu Kaisen/Season 2/05.mkv"
+///
+pub fn parse_path<P: AsRef<Path>>(path: P) -> ParsedPath {
+    ParsedPath::parse(path, &KeywordManager::new())
+}
+
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::keyword::{KeywordCategory, KeywordKind, KeywordPriority};
 
     #[test]
     fn test_remove_extension() {
@@ -33,9 +86,55 @@ mod tests {
         let tokens = tokenizer::tokenize(&input_without_extension);
 
         println!("{:#?}", tokens);
+    }
+
+    #[test]
+    fn test_parse() {
+        let parsed = parse("[SubsPlease] Jujutsu Kaisen Season 2 - 01 [1080p].mkv");
+
+        println!("{:#?}", parsed);
+
+        assert_eq!(String::from("[SubsPlease] Jujutsu Kaisen Season 2 - 01 [1080p].mkv"), parsed.file_name);
+        assert_eq!(Some(String::from("mkv")), parsed.file_extension);
+        assert_eq!(Some(String::from("SubsPlease")), parsed.release_group);
+        assert_eq!(vec![String::from("2")], parsed.seasons);
+        assert_eq!(Some(1080), parsed.video_resolution.map(|r| r.normalized_height()));
+    }
+
+    #[test]
+    fn test_parse_with_keyword_manager() {
+        let input = "[Group] Title - 01 [AMZN 1080p] [Batch].mkv";
+        assert!(parse(input).sources.is_empty());
+
+        let mut keyword_manager = KeywordManager::new();
+        keyword_manager
+            .add_group(KeywordCategory::Source, KeywordKind::Standalone, KeywordPriority::Normal, vec!["AMZN"])
+            .remove("BATCH");
+
+        let parsed = parse_with_keyword_manager(input, &keyword_manager);
+        assert_eq!(vec![String::from("AMZN")], parsed.sources);
+        assert!(parsed.release_information.is_empty());
+        assert_eq!(vec!["01"], parsed.episode_numbers);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serialize() {
+        use crate::metadata::MetadataKind;
+        use crate::token::{Token, TokenCategory};
 
-        assert!(true)
+        let parsed = parse("[SubsPlease] Jujutsu Kaisen Season 2 - 01 (25) [1080p].mkv");
+        let json = serde_json::to_value(&parsed).unwrap();
+        assert_eq!("SubsPlease", json["release_group"]);
+        assert_eq!("01", json["episode_numbers"][0]["text"]);
+        assert_eq!("progressive", json["video_resolution"]["scan_type"]);
+        assert_eq!(parsed, serde_json::from_value::<ParsedFilename>(json).unwrap());
 
+        let token = Token::new("25", TokenCategory::Known(MetadataKind::EpisodeNumberAlt));
+        let json = serde_json::to_value(&token).unwrap();
+        assert_eq!("episode_number_alt", json["category"]["known"]);
+        assert_eq!("unknown", json["kind"]);
+        assert_eq!(token, serde_json::from_value::<Token>(json).unwrap());
     }
 
     // Add more test cases as needed
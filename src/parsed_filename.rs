@@ -0,0 +1,399 @@
+use std::collections::BTreeMap;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::confidence::{FieldConfidence, Rule};
+use crate::keyword::{KeywordCategory, KeywordPriority};
+use crate::metadata::MetadataKind;
+use crate::token::{Span, Token, TokenCategory};
+use crate::token_helper::parse_release_version;
+use crate::video_resolution::VideoResolution;
+#[cfg(test)]
+use crate::parser::Parser;
+#[cfg(test)]
+use crate::tokenizer;
+
+/// e.g. "07" or "07.5" for recaps.
+/// The original text is kept, "07" and "7" have the same number.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EpisodeNumber {
+    pub number: f32,
+    pub text: String,
+}
+
+/// e.g. "01-12" in batch releases, or "S01E12-S02E01" across seasons
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EpisodeRange {
+    pub start: EpisodeNumber,
+    pub end: EpisodeNumber,
+    pub start_season: Option<String>,
+    pub end_season: Option<String>, // Same as the start season unless it is repeated, e.g. "S01E01-E03"
+}
+
+impl EpisodeNumber {
+    pub fn parse(text: &str) -> Option<EpisodeNumber> {
+        let number = text.parse::<f32>().ok()?;
+        Some(EpisodeNumber {
+            number,
+            text: text.to_string(),
+        })
+    }
+
+    pub fn is_decimal(&self) -> bool {
+        self.number.fract() != 0.0
+    }
+}
+
+impl PartialEq<&str> for EpisodeNumber {
+    fn eq(&self, other: &&str) -> bool {
+        self.text == *other
+    }
+}
+
+impl PartialEq<EpisodeNumber> for &str {
+    fn eq(&self, other: &EpisodeNumber) -> bool {
+        *self == other.text
+    }
+}
+
+///
+/// Structured result of a parse.
+/// Built from the tokens once every parser pass has run.
+///
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ParsedFilename {
+    pub file_name: String,
+    pub file_extension: Option<String>,
+    pub title: Option<String>,
+    pub episode_title: Option<String>,
+    pub release_group: Option<String>,
+    pub seasons: Vec<String>,
+    pub episode_numbers: Vec<EpisodeNumber>,
+    pub episode_numbers_alt: Vec<EpisodeNumber>, // e.g. the absolute number in "S02E01 (26)"
+    pub episode_range: Option<EpisodeRange>,
+    pub parts: Vec<String>,
+    pub volume_numbers: Vec<String>,
+    pub years: Vec<String>,
+    pub release_version: Option<u32>,
+    pub video_resolution: Option<VideoResolution>,
+    pub file_checksum: Option<String>,
+    pub anime_types: Vec<String>,
+    pub audio_terms: Vec<String>,
+    pub video_terms: Vec<String>,
+    pub sources: Vec<String>,
+    pub languages: Vec<String>,
+    pub subtitles: Vec<String>,
+    pub device_compatibility: Vec<String>,
+    pub release_information: Vec<String>,
+    pub confidence: BTreeMap<MetadataKind, FieldConfidence>, // The least confident rule of each field
+    pub spans: BTreeMap<MetadataKind, Vec<Span>>, // Where each value was found in the file name, in order
+}
+
+impl ParsedFilename {
+    pub fn from_tokens(file_name: &str, file_extension: Option<String>, tokens: &[Token]) -> ParsedFilename {
+        let mut parsed = ParsedFilename {
+            file_name: file_name.to_string(),
+            ..Default::default()
+        };
+        if let Some(start) = file_extension.as_ref().and_then(|e| file_name.rfind(e.as_str())) {
+            parsed.add_span(MetadataKind::FileExtension, Span { start, end: file_name.len() });
+        }
+        parsed.file_extension = file_extension;
+
+        for token in tokens {
+            match &token.category {
+                TokenCategory::Known(kind) => {
+                    parsed.add_known(*kind, token);
+                    parsed.add_span(*kind, token.span);
+                    if let Some(rule) = token.rule {
+                        parsed.add_confidence(*kind, rule);
+                    }
+                }
+                TokenCategory::Keyword(keyword) => {
+                    parsed.add_keyword(keyword.category, token);
+                    if let Some(kind) = get_keyword_metadata_kind(keyword.category) {
+                        let rule = if keyword.priority == KeywordPriority::Low { Rule::LowPriorityKeyword } else { Rule::Keyword };
+                        parsed.add_confidence(kind, rule);
+                        parsed.add_span(kind, token.span);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // These can span several tokens, e.g. "Tower", " ", "of", " ", "Druaga"
+        parsed.title = join_tokens_with_metadata_kind(tokens, MetadataKind::Title);
+        parsed.episode_title = join_tokens_with_metadata_kind(tokens, MetadataKind::EpisodeTitle);
+        if let Some(release_group) = join_tokens_with_metadata_kind(tokens, MetadataKind::ReleaseGroup) {
+            parsed.release_group = Some(release_group);
+        }
+        for kind in [MetadataKind::Title, MetadataKind::EpisodeTitle, MetadataKind::ReleaseGroup] {
+            if let Some(span) = get_joined_span(tokens, kind) {
+                parsed.spans.insert(kind, vec![span]);
+            }
+        }
+        parsed.episode_range = get_episode_range(tokens);
+
+        parsed
+    }
+
+    /// Whether the episode number matches either numbering scheme.
+    pub fn has_episode_number(&self, number: f32) -> bool {
+        self.episode_numbers
+            .iter()
+            .chain(self.episode_numbers_alt.iter())
+            .any(|e| e.number == number)
+    }
+
+    pub fn confidence_of(&self, kind: MetadataKind) -> Option<FieldConfidence> {
+        self.confidence.get(&kind).copied()
+    }
+
+    ///
+    /// The least confident field, e.g. to send the parse for a manual review.
+    ///
+    pub fn lowest_confidence(&self) -> Option<(MetadataKind, FieldConfidence)> {
+        self.confidence
+            .iter()
+            .min_by(|(_, a), (_, b)| a.score.total_cmp(&b.score))
+            .map(|(kind, confidence)| (*kind, *confidence))
+    }
+
+    ///
+    /// Where the field was found in the file name, e.g. to highlight it.
+    /// For a list, the first value.
+    ///
+    pub fn span_of(&self, kind: MetadataKind) -> Option<Span> {
+        self.spans.get(&kind).and_then(|s| s.first()).copied()
+    }
+
+    pub fn spans_of(&self, kind: MetadataKind) -> &[Span] {
+        self.spans.get(&kind).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Moves every span by the position of the parsed name in a longer input, see ParsedPath.
+    pub(crate) fn offset_spans(&mut self, offset: usize) {
+        for span in self.spans.values_mut().flatten() {
+            *span = span.offset_by(offset);
+        }
+    }
+
+    fn add_span(&mut self, kind: MetadataKind, span: Span) {
+        self.spans.entry(kind).or_default().push(span);
+    }
+
+    fn add_confidence(&mut self, kind: MetadataKind, rule: Rule) {
+        let confidence = self.confidence.entry(kind).or_insert(FieldConfidence::from(rule));
+        if rule.score() < confidence.score {
+            *confidence = FieldConfidence::from(rule);
+        }
+    }
+
+    fn add_known(&mut self, kind: MetadataKind, token: &Token) {
+        let value = token.value.clone();
+        match kind {
+            MetadataKind::Season => self.seasons.push(value),
+            MetadataKind::EpisodeNumber => self.episode_numbers.extend(EpisodeNumber::parse(value.as_str())),
+            MetadataKind::EpisodeNumberAlt => self.episode_numbers_alt.extend(EpisodeNumber::parse(value.as_str())),
+            MetadataKind::Part => self.parts.push(value),
+            MetadataKind::VolumeNumber => self.volume_numbers.push(value),
+            MetadataKind::Year => self.years.push(value),
+            MetadataKind::ReleaseVersion => self.release_version = self.release_version.or(parse_release_version(value.as_str())),
+            MetadataKind::VideoResolution => self.video_resolution = self.video_resolution.or(VideoResolution::parse(value.as_str())),
+            MetadataKind::FileChecksum => { self.file_checksum.get_or_insert(value); }
+            _ => {}
+        }
+    }
+
+    fn add_keyword(&mut self, category: KeywordCategory, token: &Token) {
+        let value = token.value.clone();
+        match category {
+            KeywordCategory::AnimeType => self.anime_types.push(value),
+            KeywordCategory::AudioTerm => self.audio_terms.push(value),
+            KeywordCategory::VideoTerm => self.video_terms.push(value),
+            KeywordCategory::Source => self.sources.push(value),
+            KeywordCategory::Language => self.languages.push(value),
+            KeywordCategory::Subtitles => self.subtitles.push(value),
+            KeywordCategory::DeviceCompat => self.device_compatibility.push(value),
+            KeywordCategory::ReleaseInfo => self.release_information.push(value),
+            KeywordCategory::ReleaseGroup => { self.release_group.get_or_insert(value); }
+            KeywordCategory::ReleaseVersion => self.release_version = self.release_version.or(parse_release_version(value.as_str())),
+            // Prefixes only point to other tokens
+            _ => {}
+        }
+    }
+}
+
+/// Metadata kind of the fields filled by keywords, prefixes only point to other tokens.
+fn get_keyword_metadata_kind(category: KeywordCategory) -> Option<MetadataKind> {
+    match category {
+        KeywordCategory::AnimeType => Some(MetadataKind::AnimeType),
+        KeywordCategory::AudioTerm => Some(MetadataKind::AudioTerm),
+        KeywordCategory::VideoTerm => Some(MetadataKind::VideoTerm),
+        KeywordCategory::Source => Some(MetadataKind::Source),
+        KeywordCategory::Language => Some(MetadataKind::Language),
+        KeywordCategory::Subtitles => Some(MetadataKind::Subtitles),
+        KeywordCategory::DeviceCompat => Some(MetadataKind::DeviceCompatibility),
+        KeywordCategory::ReleaseInfo => Some(MetadataKind::ReleaseInformation),
+        KeywordCategory::ReleaseGroup => Some(MetadataKind::ReleaseGroup),
+        KeywordCategory::ReleaseVersion => Some(MetadataKind::ReleaseVersion),
+        _ => None,
+    }
+}
+
+///
+/// Joins every token from the first to the last token of the given kind.
+/// Delimiters are normalized to spaces.
+///
+fn join_tokens_with_metadata_kind(tokens: &[Token], kind: MetadataKind) -> Option<String> {
+    let first = tokens.iter().position(|t| t.has_metadata_kind(kind))?;
+    let last = tokens.iter().rposition(|t| t.has_metadata_kind(kind))?;
+
+    let mut value = String::new();
+    for token in &tokens[first..=last] {
+        if token.category.is_delimiter() {
+            if !value.ends_with(' ') {
+                value.push(' ');
+            }
+        } else {
+            value.push_str(token.value.as_str());
+        }
+    }
+
+    Some(value.trim().to_string())
+}
+
+///
+/// Span from the first to the last token of the given kind, the same tokens as the joined value.
+///
+fn get_joined_span(tokens: &[Token], kind: MetadataKind) -> Option<Span> {
+    let first = tokens.iter().find(|t| t.has_metadata_kind(kind))?;
+    let last = tokens.iter().rfind(|t| t.has_metadata_kind(kind))?;
+    Some(Span::join(first.span, last.span))
+}
+
+///
+/// Two episode numbers joined by a range separator, e.g. "01", "-", "12" or "E01", "-", "E03".
+/// Episode numbers joined by "+" are not a range.
+///
+fn get_episode_range(tokens: &[Token]) -> Option<EpisodeRange> {
+    let mut episode_indices = tokens
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| t.has_metadata_kind(MetadataKind::EpisodeNumber))
+        .map(|(i, _)| i);
+    let start = episode_indices.next()?;
+    let end = episode_indices.next()?;
+
+    let is_range = tokens[start + 1..end]
+        .iter()
+        .any(|t| t.category.is_separator() && t.value != "+");
+
+    if is_range {
+        let start_season = get_season_before(tokens, start);
+        return Some(EpisodeRange {
+            start: EpisodeNumber::parse(tokens[start].value.as_str())?,
+            end: EpisodeNumber::parse(tokens[end].value.as_str())?,
+            end_season: get_season_before(tokens, end).or(start_season.clone()),
+            start_season,
+        });
+    }
+    None
+}
+
+///
+/// The season combined with the episode number, e.g. "01", "E", "12" in "S01E12".
+///
+fn get_season_before(tokens: &[Token], index: usize) -> Option<String> {
+    let season_index = index.checked_sub(2)?;
+    if tokens[season_index + 1].category.is_keyword(KeywordCategory::EpisodePrefix) && tokens[season_index].has_metadata_kind(MetadataKind::Season) {
+        return Some(tokens[season_index].value.clone());
+    }
+    None
+}
+
+//--------------------------------------------------------------------------------------------------
+
+#[test]
+fn test_episode_number() {
+    let episode_number = EpisodeNumber::parse("07.5").unwrap();
+    assert_eq!(7.5, episode_number.number);
+    assert_eq!("07.5", episode_number.text);
+    assert!(episode_number.is_decimal());
+
+    let episode_number = EpisodeNumber::parse("07").unwrap();
+    assert_eq!(7.0, episode_number.number);
+    assert!(!episode_number.is_decimal());
+
+    assert_eq!(None, EpisodeNumber::parse("v2"));
+}
+
+#[test]
+fn test_from_tokens() {
+    let input = String::from("[HorribleSubs] Tower of Druaga - Sword of Uruk - S01E04 [480p]");
+    let tokens = tokenizer::tokenize(&input);
+    let mut parser = Parser::new(tokens);
+    parser.parse();
+
+    let parsed = ParsedFilename::from_tokens(&input, None, parser.token_manager.tokens());
+    println!("{:#?}", parsed);
+
+    assert_eq!(vec!["01".to_string()], parsed.seasons);
+    assert_eq!(vec!["04"], parsed.episode_numbers);
+    assert_eq!(Some(480), parsed.video_resolution.map(|r| r.height));
+    assert_eq!(Some("HorribleSubs".to_string()), parsed.release_group);
+}
+
+#[test]
+fn test_from_tokens_keywords() {
+    let input = String::from("Violet.Evergarden.The.Movie.1080p.Dual.Audio.BDRip.10.bits.DD.x265-EMBER");
+    let tokens = tokenizer::tokenize(&input);
+    let mut parser = Parser::new(tokens);
+    parser.parse();
+
+    let parsed = ParsedFilename::from_tokens(&input, None, parser.token_manager.tokens());
+    println!("{:#?}", parsed);
+
+    assert_eq!(Some(1080), parsed.video_resolution.map(|r| r.height));
+    assert_eq!(vec!["Movie".to_string()], parsed.anime_types);
+    assert_eq!(vec!["x265".to_string()], parsed.video_terms);
+}
+
+#[test]
+fn test_confidence() {
+    let parsed = crate::parse("[Group] Title S01E04 [BD 1080p] END");
+
+    assert_eq!(Some(FieldConfidence { score: 1.0, rule: Rule::CombinedSeasonEpisode }), parsed.confidence_of(MetadataKind::EpisodeNumber));
+    assert_eq!(Some(Rule::LeadingBrackets), parsed.confidence_of(MetadataKind::ReleaseGroup).map(|c| c.rule));
+    assert_eq!(Some(Rule::Keyword), parsed.confidence_of(MetadataKind::Source).map(|c| c.rule));
+    assert_eq!(None, parsed.confidence_of(MetadataKind::EpisodeTitle));
+    assert_eq!(Some((MetadataKind::ReleaseInformation, FieldConfidence::from(Rule::LowPriorityKeyword))), parsed.lowest_confidence());
+}
+
+#[test]
+fn test_spans() {
+    let input = "/Anime/[Group] Tower of Druaga - S01E04v2 [BD 1080p].mkv";
+    let parsed = crate::parse(input);
+    let slice_of = |kind: MetadataKind| parsed.span_of(kind).and_then(|s| s.slice_of(input));
+
+    assert_eq!(Some("Group"), slice_of(MetadataKind::ReleaseGroup));
+    assert_eq!(Some("Tower of Druaga"), slice_of(MetadataKind::Title));
+    assert_eq!(Some("01"), slice_of(MetadataKind::Season));
+    assert_eq!(Some("04"), slice_of(MetadataKind::EpisodeNumber));
+    assert_eq!(Some("v2"), slice_of(MetadataKind::ReleaseVersion));
+    assert_eq!(Some("BD"), slice_of(MetadataKind::Source));
+    assert_eq!(Some("1080p"), slice_of(MetadataKind::VideoResolution));
+    assert_eq!(Some("mkv"), slice_of(MetadataKind::FileExtension));
+    assert_eq!(None, parsed.span_of(MetadataKind::Year));
+    assert!(parsed.spans_of(MetadataKind::Year).is_empty());
+
+    // Fused and overwritten tokens cover their original text
+    let input = "Title 2nd Season - 07.5";
+    let parsed = crate::parse(input);
+    assert_eq!(Some("2nd"), parsed.span_of(MetadataKind::Season).and_then(|s| s.slice_of(input)));
+    assert_eq!(Some("07.5"), parsed.span_of(MetadataKind::EpisodeNumber).and_then(|s| s.slice_of(input)));
+}
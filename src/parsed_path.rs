@@ -0,0 +1,225 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::keyword_manager::KeywordManager;
+use crate::metadata::MetadataKind;
+use crate::parsed_filename::ParsedFilename;
+use crate::tokenizer;
+
+// Episode related fields only come from the file name, e.g. not from a "Title - 01-12" batch folder
+static FOLDER_FIELDS: [MetadataKind; 11] = [
+    MetadataKind::Title,
+    MetadataKind::ReleaseGroup,
+    MetadataKind::Season,
+    MetadataKind::Year,
+    MetadataKind::VideoResolution,
+    MetadataKind::AnimeType,
+    MetadataKind::AudioTerm,
+    MetadataKind::VideoTerm,
+    MetadataKind::Source,
+    MetadataKind::Language,
+    MetadataKind::Subtitles,
+];
+
+// Library folders, e.g. "/mnt/media/Anime/Season 2/05.mkv", compared case-insensitively
+static GENERIC_FOLDERS: [&str; 14] = [
+    "anime",
+    "animes",
+    "completed",
+    "download",
+    "downloads",
+    "library",
+    "media",
+    "movies",
+    "series",
+    "shows",
+    "torrents",
+    "tv",
+    "tv shows",
+    "videos",
+];
+
+///
+/// Result of a parse of a full path, e.g. "/Anime/Jujutsu Kaisen/Season 2/05.mkv".
+/// Fields missing from the file name are filled from its parent folders, nearest first.
+/// Only the season folders and the folder of the show are used, the walk stops at the first other
+/// folder or at a generic library folder (e.g. "Downloads").
+/// All spans are byte offsets into `path`, including the ones of fields found in a folder.
+///
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ParsedPath {
+    pub path: String,
+    pub parsed: ParsedFilename,
+    pub field_sources: BTreeMap<MetadataKind, String>, // The path component each value came from, e.g. Title -> "Jujutsu Kaisen"
+}
+
+impl ParsedPath {
+    pub fn parse<P: AsRef<Path>>(path: P, keyword_manager: &KeywordManager) -> ParsedPath {
+        let path = path.as_ref();
+        let file_name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+
+        let path_string = path.to_string_lossy().to_string();
+        let mut parsed = crate::parse_with_keyword_manager(&file_name, keyword_manager);
+        parsed.offset_spans(tokenizer::file_name_offset(&path_string));
+
+        let mut parsed_path = ParsedPath {
+            path: path_string,
+            parsed,
+            field_sources: BTreeMap::new(),
+        };
+        // Every field found in the file name has a span, e.g. the episode number or the checksum
+        for kind in parsed_path.parsed.spans.keys() {
+            parsed_path.field_sources.insert(*kind, file_name.clone());
+        }
+
+        for folder_path in path.ancestors().skip(1) {
+            let Some(folder_name) = folder_path.file_name() else {
+                break;
+            };
+            let folder_name = folder_name.to_string_lossy().to_string();
+            if is_generic_folder(&folder_name) {
+                break;
+            }
+            let mut folder = crate::parse_with_keyword_manager(&folder_name, keyword_manager);
+            // The ancestor is a prefix of the path, e.g. "/Anime/Jujutsu Kaisen/Season 2"
+            folder.offset_spans(tokenizer::file_name_offset(&folder_path.to_string_lossy()));
+            parsed_path.fill_from(&folder_name, &folder);
+            // A season folder is inside the folder of its show, e.g. "Jujutsu Kaisen/Season 2"
+            if !is_season_folder(&folder) {
+                break;
+            }
+        }
+
+        parsed_path
+    }
+
+    /// Where the value of the field came from, e.g. "Season 2"
+    pub fn source_of(&self, kind: MetadataKind) -> Option<&str> {
+        self.field_sources.get(&kind).map(String::as_str)
+    }
+
+    fn fill_from(&mut self, folder_name: &str, folder: &ParsedFilename) {
+        for kind in FOLDER_FIELDS {
+            if !has_field(&self.parsed, kind) && has_field(folder, kind) {
+                copy_field(&mut self.parsed, folder, kind);
+                if let Some(confidence) = folder.confidence_of(kind) {
+                    self.parsed.confidence.insert(kind, confidence);
+                }
+                match folder.spans.get(&kind) {
+                    Some(spans) => self.parsed.spans.insert(kind, spans.clone()),
+                    None => self.parsed.spans.remove(&kind),
+                };
+                self.field_sources.insert(kind, folder_name.to_string());
+            }
+        }
+    }
+}
+
+fn is_generic_folder(folder_name: &str) -> bool {
+    GENERIC_FOLDERS.contains(&folder_name.trim().to_lowercase().as_str())
+}
+
+/// e.g. "Season 2" or "S02", but not "Jujutsu Kaisen Season 2"
+fn is_season_folder(folder: &ParsedFilename) -> bool {
+    !folder.seasons.is_empty() && folder.title.is_none()
+}
+
+fn has_field(parsed: &ParsedFilename, kind: MetadataKind) -> bool {
+    match kind {
+        MetadataKind::Title => parsed.title.is_some(),
+        MetadataKind::ReleaseGroup => parsed.release_group.is_some(),
+        MetadataKind::Season => !parsed.seasons.is_empty(),
+        MetadataKind::Year => !parsed.years.is_empty(),
+        MetadataKind::VideoResolution => parsed.video_resolution.is_some(),
+        MetadataKind::AnimeType => !parsed.anime_types.is_empty(),
+        MetadataKind::AudioTerm => !parsed.audio_terms.is_empty(),
+        MetadataKind::VideoTerm => !parsed.video_terms.is_empty(),
+        MetadataKind::Source => !parsed.sources.is_empty(),
+        MetadataKind::Language => !parsed.languages.is_empty(),
+        MetadataKind::Subtitles => !parsed.subtitles.is_empty(),
+        _ => false,
+    }
+}
+
+fn copy_field(target: &mut ParsedFilename, source: &ParsedFilename, kind: MetadataKind) {
+    match kind {
+        MetadataKind::Title => target.title = source.title.clone(),
+        MetadataKind::ReleaseGroup => target.release_group = source.release_group.clone(),
+        MetadataKind::Season => target.seasons = source.seasons.clone(),
+        MetadataKind::Year => target.years = source.years.clone(),
+        MetadataKind::VideoResolution => target.video_resolution = source.video_resolution,
+        MetadataKind::AnimeType => target.anime_types = source.anime_types.clone(),
+        MetadataKind::AudioTerm => target.audio_terms = source.audio_terms.clone(),
+        MetadataKind::VideoTerm => target.video_terms = source.video_terms.clone(),
+        MetadataKind::Source => target.sources = source.sources.clone(),
+        MetadataKind::Language => target.languages = source.languages.clone(),
+        MetadataKind::Subtitles => target.subtitles = source.subtitles.clone(),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::confidence::Rule;
+
+    #[test]
+    fn test_parse_season_folder() {
+        let parsed_path = ParsedPath::parse("/Anime/Jujutsu Kaisen/Season 2/05.mkv", &KeywordManager::new());
+
+        assert_eq!("05.mkv", parsed_path.parsed.file_name);
+        assert_eq!(vec!["05"], parsed_path.parsed.episode_numbers);
+        assert_eq!(Some(String::from("Jujutsu Kaisen")), parsed_path.parsed.title);
+        assert_eq!(vec![String::from("2")], parsed_path.parsed.seasons);
+        assert_eq!(Some("Jujutsu Kaisen"), parsed_path.source_of(MetadataKind::Title));
+        assert_eq!(Some("Season 2"), parsed_path.source_of(MetadataKind::Season));
+        assert_eq!(Some(Rule::Prefix), parsed_path.parsed.confidence_of(MetadataKind::Season).map(|c| c.rule));
+        // Spans point into the full path
+        let span_of = |kind| parsed_path.parsed.span_of(kind).and_then(|s| s.slice_of(&parsed_path.path));
+        assert_eq!(Some("2"), span_of(MetadataKind::Season));
+        assert_eq!(Some("Jujutsu Kaisen"), span_of(MetadataKind::Title));
+        assert_eq!(Some("05"), span_of(MetadataKind::EpisodeNumber));
+        assert_eq!(Some("mkv"), span_of(MetadataKind::FileExtension));
+    }
+
+    #[test]
+    fn test_parse_release_folder() {
+        let parsed_path = ParsedPath::parse("[Group] Title (BD 1080p)/Title - 05 [ABCD1234].mkv", &KeywordManager::new());
+
+        assert_eq!(Some(String::from("Title")), parsed_path.parsed.title);
+        assert_eq!(Some(String::from("Group")), parsed_path.parsed.release_group);
+        assert_eq!(vec![String::from("BD")], parsed_path.parsed.sources);
+        assert_eq!(Some(1080), parsed_path.parsed.video_resolution.map(|r| r.height));
+        assert_eq!(Some(String::from("ABCD1234")), parsed_path.parsed.file_checksum);
+
+        // The file name takes precedence
+        assert_eq!(Some("Title - 05 [ABCD1234].mkv"), parsed_path.source_of(MetadataKind::Title));
+        assert_eq!(Some("[Group] Title (BD 1080p)"), parsed_path.source_of(MetadataKind::ReleaseGroup));
+        assert_eq!(Some("Title - 05 [ABCD1234].mkv"), parsed_path.source_of(MetadataKind::EpisodeNumber));
+        assert_eq!(Some("Title - 05 [ABCD1234].mkv"), parsed_path.source_of(MetadataKind::FileChecksum));
+        assert_eq!(None, parsed_path.source_of(MetadataKind::EpisodeTitle));
+    }
+
+    #[test]
+    fn test_parse_library_folders() {
+        let parsed_path = ParsedPath::parse("/mnt/media/Anime/Season 2/05.mkv", &KeywordManager::new());
+        assert_eq!(None, parsed_path.parsed.title);
+        assert_eq!(vec![String::from("2")], parsed_path.parsed.seasons);
+
+        let parsed_path = ParsedPath::parse("/home/me/Downloads/[Group] - 05 [1080p].mkv", &KeywordManager::new());
+        assert_eq!(None, parsed_path.parsed.title);
+        assert_eq!(Some(String::from("Group")), parsed_path.parsed.release_group);
+        assert_eq!(vec!["05"], parsed_path.parsed.episode_numbers);
+    }
+
+    #[test]
+    fn test_parse_stops_at_show_folder() {
+        let parsed_path = ParsedPath::parse("/Old Title (2019)/Jujutsu Kaisen/05.mkv", &KeywordManager::new());
+        assert_eq!(Some(String::from("Jujutsu Kaisen")), parsed_path.parsed.title);
+        assert!(parsed_path.parsed.years.is_empty());
+    }
+}
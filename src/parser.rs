@@ -1,10 +1,14 @@
 #![allow(dead_code)]
 
+use crate::confidence::Rule;
 use crate::keyword::{KeywordCategory, KeywordKind, KeywordPriority};
 use crate::metadata::MetadataKind;
-use crate::token::{Token, TokenCategory, TokenKind};
-use crate::token_helper::{is_crc32, is_video_resolution, number_is_zero_padded};
+use crate::token::{set_consecutive_spans, Span, Token, TokenCategory, TokenId};
+use crate::token::TokenKind;
+use crate::token_helper::{is_crc32, is_video_resolution, number_is_zero_padded, parse_ordinal_number, parse_release_version, split_episode_number};
+use crate::keyword_manager::KeywordManager;
 use crate::token_manager::TokenManager;
+#[cfg(test)]
 use crate::tokenizer;
 
 #[derive(Debug)]
@@ -14,8 +18,15 @@ pub struct Parser {
 
 impl Parser {
     pub fn new(tokens: Vec<Token>) -> Parser {
+        Parser::new_with_keyword_manager(tokens, KeywordManager::new())
+    }
+
+    ///
+    /// Parser using custom keywords, e.g. additional sources or release groups.
+    ///
+    pub fn new_with_keyword_manager(tokens: Vec<Token>, keyword_manager: KeywordManager) -> Parser {
         Parser {
-            token_manager: TokenManager::new(tokens)
+            token_manager: TokenManager::new_with_keyword_manager(tokens, keyword_manager)
         }
     }
 
@@ -26,41 +37,67 @@ impl Parser {
 
         self.normalize_keywords();
 
+        self.parse_release_group();
+
+        self.parse_year();
+
         self.parse_season();
 
-        // other things
+        self.parse_volume_and_part();
+
+        self.parse_episode_range();
+
+        self.parse_episode();
+
+        self.parse_multi_episode();
+
+        self.parse_episode_alt();
+
+        self.parse_title();
 
-        // self.parse_keywords(KeywordPriority::Low);
+        // Low priority keywords can only claim tokens that weren't identified as something else,
+        // e.g. "Final" in "Final Fantasy" is part of the title
+        self.parse_keywords(KeywordPriority::Low);
+        // e.g. a low priority "PV" combined with its number, "PV2" -> "PV", "2"
+        self.normalize_keywords();
+
+        self.parse_episode_title();
     }
 
     fn parse_season(&mut self) {
         if self.token_manager.has_token_with_metadata_kind(MetadataKind::Season) {
-            return ();
+            return;
         }
 
-        let tokens = self.token_manager.get_tokens();
-        let mut iterator = tokens.iter();
         // Get SeasonPrefix
-        if let Some(season_prefix_token) = iterator.find(|t| t.category.is_keyword(KeywordCategory::SeasonPrefix)) {
+        let season_prefix_token = self.token_manager.tokens().iter().find(|t| t.category.is_keyword(KeywordCategory::SeasonPrefix)).cloned();
+        if let Some(season_prefix_token) = &season_prefix_token {
             // Get Keyword from it
             if let Some(keyword) = season_prefix_token.category.get_keyword() {
                 match keyword.kind {
                     KeywordKind::Standalone => {} // invalid
-                    KeywordKind::Combined { .. } => { // e.g. S01
-                        // check
+                    KeywordKind::Combined { .. } => { // e.g. S01 or S1-S3
+                        if let Some(first_number) = self.get_number_right_after(season_prefix_token) {
+                            self.token_manager.assign_metadata_kind(first_number.id, MetadataKind::Season, Rule::Prefix);
+                            if let Some(last_number) = self.get_season_range_end_after(&first_number) {
+                                self.token_manager.assign_metadata_kind(last_number.id, MetadataKind::Season, Rule::PrefixRange);
+                            }
+                        }
                     }
-                    KeywordKind::OrdinalSuffix => {
-
+                    KeywordKind::OrdinalSuffix => { // e.g. 2nd Season
+                        if let Some(ordinal_token) = self.get_token_before(season_prefix_token) {
+                            self.assign_ordinal_season(&ordinal_token, Rule::OrdinalPrefix);
+                        }
                     }
                     _ => { // e.g. Season 1 or Seasons 1-4
                         // Check range
-                        loop {
+                        'season: {
                             if let Some(number_range_tokens) = self.get_number_range_after(season_prefix_token) {
                                 // If the keyword ends with "s" (e.g. Seasons), then we are sure that it is a range
                                 if keyword.value.ends_with("S") {
-                                    self.token_manager.update_token_category(number_range_tokens[0].uuid, TokenCategory::Known(MetadataKind::Season));
-                                    self.token_manager.update_token_category(number_range_tokens[1].uuid, TokenCategory::Known(MetadataKind::Season));
-                                    break;
+                                    self.token_manager.assign_metadata_kind(number_range_tokens[0].id, MetadataKind::Season, Rule::PrefixRange);
+                                    self.token_manager.assign_metadata_kind(number_range_tokens[1].id, MetadataKind::Season, Rule::PrefixRange);
+                                    break 'season;
                                 }
                                 let first_number_is_zero_padded = number_is_zero_padded(number_range_tokens[0].value.as_str());
                                 let second_number_is_zero_padded = number_is_zero_padded(number_range_tokens[1].value.as_str());
@@ -68,73 +105,528 @@ impl Parser {
                                 // Check that the right side of the range isn't an episode number
                                 // e.g. If we encounter this "1 - 03" then 05 might be the episode number,
                                 if !first_number_is_zero_padded && second_number_is_zero_padded {
-                                    self.token_manager.update_token_category(number_range_tokens[0].uuid, TokenCategory::Known(MetadataKind::Season));
-                                    self.token_manager.update_token_category(number_range_tokens[1].uuid, TokenCategory::Known(MetadataKind::EpisodeNumber));
-                                    break;
+                                    self.token_manager.assign_metadata_kind(number_range_tokens[0].id, MetadataKind::Season, Rule::SeasonEpisodePadding);
+                                    self.token_manager.assign_metadata_kind(number_range_tokens[1].id, MetadataKind::EpisodeNumber, Rule::SeasonEpisodePadding);
+                                    break 'season;
                                 } else if first_number_is_zero_padded && second_number_is_zero_padded {
 
                                     // "01 - 03" (with delimiter) -> Season & Episode
-                                    if let Some(_) = self.get_delimiter_before(&number_range_tokens[1]) {
-                                        self.token_manager.update_token_category(number_range_tokens[0].uuid, TokenCategory::Known(MetadataKind::Season));
-                                        self.token_manager.update_token_category(number_range_tokens[1].uuid, TokenCategory::Known(MetadataKind::EpisodeNumber));
-                                        break;
+                                    if self.get_delimiter_before(&number_range_tokens[1]).is_some() {
+                                        self.token_manager.assign_metadata_kind(number_range_tokens[0].id, MetadataKind::Season, Rule::SeasonEpisodeDelimiter);
+                                        self.token_manager.assign_metadata_kind(number_range_tokens[1].id, MetadataKind::EpisodeNumber, Rule::SeasonEpisodeDelimiter);
+                                        break 'season;
                                     }
                                     // "01-03" (without delimiter) -> Season & Season
-                                    self.token_manager.update_token_category(number_range_tokens[0].uuid, TokenCategory::Known(MetadataKind::Season));
-                                    self.token_manager.update_token_category(number_range_tokens[1].uuid, TokenCategory::Known(MetadataKind::Season));
-                                    break;
+                                    self.token_manager.assign_metadata_kind(number_range_tokens[0].id, MetadataKind::Season, Rule::SeasonRangeUndelimited);
+                                    self.token_manager.assign_metadata_kind(number_range_tokens[1].id, MetadataKind::Season, Rule::SeasonRangeUndelimited);
+                                    break 'season;
                                 } else {
                                     // if we encounter "[1,01] - 12" (>= 10) (with or without delimiters) -> Season & Episode
                                     if let Ok(second_number) = number_range_tokens[1].value.parse::<u32>() {
                                         if second_number > 10 {
-                                            self.token_manager.update_token_category(number_range_tokens[0].uuid, TokenCategory::Known(MetadataKind::Season));
-                                            self.token_manager.update_token_category(number_range_tokens[1].uuid, TokenCategory::Known(MetadataKind::EpisodeNumber));
+                                            self.token_manager.assign_metadata_kind(number_range_tokens[0].id, MetadataKind::Season, Rule::SeasonEpisodeLargeNumber);
+                                            self.token_manager.assign_metadata_kind(number_range_tokens[1].id, MetadataKind::EpisodeNumber, Rule::SeasonEpisodeLargeNumber);
+                                            break 'season;
                                         }
                                     }
                                     // if we encounter "1 - 5" (<10) (with or without delimiters) -> Season & Season
-                                    self.token_manager.update_token_category(number_range_tokens[0].uuid, TokenCategory::Known(MetadataKind::Season));
-                                    self.token_manager.update_token_category(number_range_tokens[1].uuid, TokenCategory::Known(MetadataKind::Season));
-                                    break;
+                                    self.token_manager.assign_metadata_kind(number_range_tokens[0].id, MetadataKind::Season, Rule::SeasonRangeSmallNumbers);
+                                    self.token_manager.assign_metadata_kind(number_range_tokens[1].id, MetadataKind::Season, Rule::SeasonRangeSmallNumbers);
+                                    break 'season;
                                 }
                             };
                             if let Some(number_token) = self.get_number_or_like_after(season_prefix_token) {
-                                self.token_manager.update_token_category(number_token.uuid, TokenCategory::Known(MetadataKind::Season));
-                                break;
+                                // e.g. "Season 2nd"
+                                if !self.assign_ordinal_season(&number_token, Rule::Prefix) {
+                                    self.token_manager.assign_metadata_kind(number_token.id, MetadataKind::Season, Rule::Prefix);
+                                }
+                                break 'season;
                             }
-                            break;
+                            break 'season;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    ///
+    /// Replaces an ordinal number with the season it stands for, e.g. "2nd" -> "2".
+    /// Returns false if the token isn't an ordinal number.
+    ///
+    fn assign_ordinal_season(&mut self, ordinal_token: &Token, rule: Rule) -> bool {
+        let Some(season) = parse_ordinal_number(ordinal_token.value.as_str()) else {
+            return false;
+        };
+        self.token_manager.overwrite_token(
+            ordinal_token.id,
+            // The span still covers "2nd"
+            Token::new_with_kind(season.to_string(), TokenCategory::Known(MetadataKind::Season), TokenKind::Number)
+                .with_rule(rule)
+                .with_span(ordinal_token.span.start, ordinal_token.span.end),
+        );
+        true
+    }
+
+    ///
+    /// Parse volume and part numbers following their prefix.
+    /// e.g. "Vol.3", "Volume 01-04", "Part 2", "Cour 2"
+    ///
+    fn parse_volume_and_part(&mut self) {
+        self.parse_number_after_prefix(KeywordCategory::VolumePrefix, MetadataKind::VolumeNumber);
+        self.parse_number_after_prefix(KeywordCategory::PartPrefix, MetadataKind::Part);
+    }
+
+    fn parse_number_after_prefix(&mut self, prefix_category: KeywordCategory, metadata_kind: MetadataKind) {
+        if self.token_manager.has_token_with_metadata_kind(metadata_kind) {
+            return;
+        }
+
+        let prefix_tokens: Vec<Token> = self.token_manager.tokens().iter().filter(|t| t.category.is_keyword(prefix_category)).cloned().collect();
+        for prefix_token in prefix_tokens.iter() {
+            // e.g. "Volume 01-04" or "Volumes 1 - 4", but not "Part 2 - 05" where 05 is the episode number
+            if let Some(number_range_tokens) = self.get_number_range_after(prefix_token) {
+                let is_plural = prefix_token.value.to_uppercase().ends_with('S');
+                if is_plural || self.get_delimiter_before(&number_range_tokens[1]).is_none() {
+                    self.token_manager.assign_metadata_kind(number_range_tokens[0].id, metadata_kind, Rule::PrefixRange);
+                    self.token_manager.assign_metadata_kind(number_range_tokens[1].id, metadata_kind, Rule::PrefixRange);
+                    return;
+                }
+            }
+            // e.g. "Vol.3"
+            if let Some(number_token) = self.get_number_or_like_after(prefix_token) {
+                self.token_manager.assign_metadata_kind(number_token.id, metadata_kind, Rule::Prefix);
+                return;
+            }
+        }
+    }
+
+    ///
+    /// Parse an episode range, e.g. batch releases.
+    /// e.g. "Title (01-12)", "Title 01~13", "Title 01 to 24", "Title E01-E03", "S01E01-E03"
+    ///
+    fn parse_episode_range(&mut self) {
+        let tokens: Vec<&Token> = self.token_manager.tokens().iter().filter(|t| !t.category.is_delimiter()).collect();
+
+        for (index, start_token) in tokens.iter().enumerate() {
+            let is_episode_number = start_token.has_metadata_kind(MetadataKind::EpisodeNumber);
+            let is_unknown_number = start_token.category.is_unknown() && start_token.kind.is_numeric();
+            if !is_episode_number && !is_unknown_number {
+                continue;
+            }
+
+            let separator_token = match tokens.get(index + 1) {
+                Some(token) if is_range_separator(token) => token,
+                _ => continue,
+            };
+
+            // e.g. "E01-E03"
+            let mut end_index = index + 2;
+            let is_prefixed = tokens.get(end_index).is_some_and(|t| t.category.is_keyword(KeywordCategory::EpisodePrefix));
+            if is_prefixed {
+                end_index += 1;
+            }
+
+            let end_token = match tokens.get(end_index) {
+                Some(token) if token.category.is_unknown() && token.kind.is_numeric() => token,
+                _ => continue,
+            };
+
+            match (start_token.value.parse::<u32>(), end_token.value.parse::<u32>()) {
+                (Ok(start), Ok(end)) if start < end => {}
+                _ => continue,
+            }
+
+            // " - " usually separates the title from the episode number, e.g. "Title 2 - 05"
+            // so we only accept it when the range is enclosed or uses an episode prefix.
+            let is_spaced_dash = separator_token.value == "-" && self.get_delimiter_before(separator_token).is_some();
+            if is_spaced_dash && !is_episode_number && !is_prefixed && !start_token.is_enclosed() {
+                continue;
+            }
+
+            let (start_id, end_id) = (start_token.id, end_token.id);
+            if separator_token.category.is_unknown() {
+                // e.g. "to"
+                self.token_manager.update_token_category(separator_token.id, TokenCategory::Separator);
+            }
+            self.token_manager.assign_metadata_kind(start_id, MetadataKind::EpisodeNumber, Rule::EpisodeRange);
+            self.token_manager.assign_metadata_kind(end_id, MetadataKind::EpisodeNumber, Rule::EpisodeRange);
+            return;
+        }
+    }
+
+    ///
+    /// Parse a standalone episode number, when it wasn't found alongside the season (e.g. S01E04).
+    /// e.g. "Title - 04 [1080p]", "Title_04_[720p]", "Title - 04v2"
+    ///
+    fn parse_episode(&mut self) {
+        if self.token_manager.has_token_with_metadata_kind(MetadataKind::EpisodeNumber) {
+            return;
+        }
+
+        let tokens = self.token_manager.tokens();
+        let candidates: Vec<&Token> = tokens
+            .iter()
+            .filter(|t| t.category.is_unknown() && !t.is_enclosed() && t.kind.is_numeric_or_like())
+            .collect();
+        let numbers: Vec<&Token> = candidates
+            .iter()
+            .filter(|t| t.kind.is_numeric() && t.value.len() <= 4)
+            .copied()
+            .collect();
+
+        // e.g. "Title - 07.5 [720p]", the decimal parts are fused into one token
+        // Before the version is split, so that "07.5v2" isn't taken for "07", ".", "5", "v2"
+        for token in numbers.iter().rev() {
+            if let Some(decimal_tokens) = self.get_decimal_number_at(token) {
+                if self.previous_token_is_separator(token) && !self.next_token_is_unknown(&decimal_tokens[2]) {
+                    if let Some(index) = self.token_manager.get_index_of_token(token, false) {
+                        let (fraction, version) = match split_episode_number(decimal_tokens[2].value.as_str()) {
+                            Some((fraction, version)) => (fraction, Some(version)),
+                            None => (decimal_tokens[2].value.clone(), None),
+                        };
+                        let value = format!("{}{}{}", decimal_tokens[0].value, decimal_tokens[1].value, fraction);
+                        let span = Span::join(decimal_tokens[0].span, decimal_tokens[2].span);
+                        let decimal_token = Token::new_with_kind(value, TokenCategory::Known(MetadataKind::EpisodeNumber), TokenKind::NumberLike)
+                            .with_rule(Rule::DecimalEpisode)
+                            .with_span(span.start, span.end);
+                        self.token_manager.fuse_tokens_at(index, decimal_tokens.len(), decimal_token.clone());
+                        if let Some(version) = version {
+                            let mut token_parts = vec![
+                                decimal_token,
+                                Token::new_with_kind(version, TokenCategory::Known(MetadataKind::ReleaseVersion), TokenKind::String).with_rule(Rule::EpisodeVersion),
+                            ];
+                            set_consecutive_spans(&mut token_parts, span);
+                            self.token_manager.flatten_token_at(index, token_parts);
                         }
+                        return;
+                    }
+                }
+            }
+        }
+
+        // e.g. "04v2" or "04'", the version is split from the episode number
+        for token in candidates.iter().filter(|t| t.kind.is_number_like()) {
+            if let Some((number, version)) = split_episode_number(token.value.as_str()) {
+                if let Some(index) = self.token_manager.get_index_of_token(token, false) {
+                    let mut token_parts = vec![
+                        Token::new_with_kind(number, TokenCategory::Known(MetadataKind::EpisodeNumber), TokenKind::Number).with_rule(Rule::EpisodeVersion),
+                        Token::new_with_kind(version, TokenCategory::Known(MetadataKind::ReleaseVersion), TokenKind::String).with_rule(Rule::EpisodeVersion),
+                    ];
+                    set_consecutive_spans(&mut token_parts, token.span);
+                    self.token_manager.flatten_token_at(index, token_parts);
+                    return;
+                }
+            }
+        }
+
+        // e.g. "Title E05 [1080p]", "Title Episode 5"
+        for prefix_token in tokens.iter().filter(|t| t.category.is_keyword(KeywordCategory::EpisodePrefix)) {
+            if let Some(number_token) = self.get_number_or_like_after(prefix_token) {
+                if number_token.category.is_unknown() && number_token.kind.is_numeric() {
+                    self.token_manager.assign_metadata_kind(number_token.id, MetadataKind::EpisodeNumber, Rule::Prefix);
+                    return;
+                }
+            }
+        }
+
+        // e.g. "Title - 04 [1080p]" or "Title - 04 - Episode Title"
+        // Start from the end since the title is more likely to contain numbers
+        for token in numbers.iter().rev() {
+            if self.previous_token_is_separator(token) && !self.next_token_is_unknown(token) {
+                self.token_manager.assign_metadata_kind(token.id, MetadataKind::EpisodeNumber, Rule::NumberAfterSeparator);
+                return;
+            }
+        }
+
+        // e.g. "05.mkv" in a season folder, the file name is only the episode number
+        if let [token] = numbers[..] {
+            if tokens.iter().all(|t| t.id == token.id || t.category.is_delimiter()) {
+                self.token_manager.assign_metadata_kind(token.id, MetadataKind::EpisodeNumber, Rule::NumberOnly);
+                return;
+            }
+        }
+
+        // e.g. "Title_04_[720p]", the last number before the metadata brackets.
+        // The number shouldn't be the first word, otherwise it is part of the title (e.g. "86 [1080p]")
+        for token in numbers.iter().rev() {
+            if self.previous_token_is_unknown(token) && self.next_token_is_bracket_or_end(token) {
+                self.token_manager.assign_metadata_kind(token.id, MetadataKind::EpisodeNumber, Rule::NumberBeforeMetadata);
+                return;
+            }
+        }
+    }
+
+    ///
+    /// Parse the release group.
+    /// It is either the leading enclosed group (e.g. "[Erai-raws] Title") or the scene-style suffix
+    /// after the last separator (e.g. "...DD.x265-EMBER").
+    ///
+    fn parse_release_group(&mut self) {
+        if self.token_manager.has_token_with_metadata_kind(MetadataKind::ReleaseGroup) {
+            return;
+        }
+
+        let tokens = self.token_manager.tokens();
+
+        // e.g. "[Erai-raws] Title" -> "Erai", "raws"
+        if tokens.iter().find(|t| !t.category.is_delimiter()).is_some_and(|t| t.category.is_opening_bracket()) {
+            let enclosed_tokens: Vec<&Token> = tokens
+                .iter()
+                .skip_while(|t| !t.category.is_opening_bracket())
+                .skip(1)
+                .take_while(|t| !t.category.is_closing_bracket())
+                .filter(|t| !t.category.is_delimiter() && !t.category.is_separator())
+                .collect();
+
+            // Keywords (e.g. "[1080p]", or low priority ones not identified yet like "[END]"), versions (e.g. "[v2]")
+            // or numbers only (e.g. "[01]") are not release groups
+            let is_release_group = !enclosed_tokens.is_empty()
+                && enclosed_tokens.iter().all(|t| t.category.is_unknown() && self.token_manager.identify_keyword(t).is_none())
+                && !enclosed_tokens.iter().any(|t| parse_release_version(t.value.as_str()).is_some())
+                && !enclosed_tokens.iter().all(|t| t.kind.is_numeric());
+
+            if is_release_group {
+                let enclosed_ids: Vec<TokenId> = enclosed_tokens.iter().map(|t| t.id).collect();
+                for id in enclosed_ids {
+                    self.token_manager.assign_metadata_kind(id, MetadataKind::ReleaseGroup, Rule::LeadingBrackets);
+                }
+                return;
+            }
+        }
+
+        // e.g. "...x265-EMBER", the group is attached to a keyword by a separator
+        let last_tokens: Vec<&Token> = tokens.iter().rev().take(3).collect();
+        if let [group_token, separator_token, previous_token] = last_tokens[..] {
+            if group_token.category.is_unknown()
+                && !group_token.is_enclosed()
+                && group_token.kind.is_string()
+                && separator_token.category.is_separator()
+                && (previous_token.category.is_any_keyword() || previous_token.category.is_known()) {
+                self.token_manager.assign_metadata_kind(group_token.id, MetadataKind::ReleaseGroup, Rule::SceneSuffix);
+            }
+        }
+    }
+
+    ///
+    /// Parse the other episode numbers of multi-episode files, joined by "+".
+    /// e.g. "Title - 01+02", "Title E01+E02+E03"
+    ///
+    fn parse_multi_episode(&mut self) {
+        loop {
+            let tokens: Vec<&Token> = self.token_manager.tokens().iter().filter(|t| !t.category.is_delimiter()).collect();
+
+            let is_unknown_number = |t: &Token| t.category.is_unknown() && t.kind.is_numeric();
+            let is_plus = |t: &Token| t.category.is_separator() && t.value == "+";
+
+            let mut next_episode: Option<TokenId> = None;
+            for (index, token) in tokens.iter().enumerate() {
+                if !token.has_metadata_kind(MetadataKind::EpisodeNumber) {
+                    continue;
+                }
+
+                // e.g. "01+02" or "E01+E02", after the episode number
+                if tokens.get(index + 1).is_some_and(|t| is_plus(t)) {
+                    let mut after_index = index + 2;
+                    if tokens.get(after_index).is_some_and(|t| t.category.is_keyword(KeywordCategory::EpisodePrefix)) {
+                        after_index += 1;
+                    }
+                    if let Some(after_token) = tokens.get(after_index).filter(|t| is_unknown_number(t)) {
+                        next_episode = Some(after_token.id);
+                        break;
                     }
                 }
+
+                // e.g. "01+02", before the episode number
+                if index >= 2 && is_plus(tokens[index - 1]) && is_unknown_number(tokens[index - 2]) {
+                    next_episode = Some(tokens[index - 2].id);
+                    break;
+                }
+            }
+
+            match next_episode {
+                Some(id) => self.token_manager.assign_metadata_kind(id, MetadataKind::EpisodeNumber, Rule::MultiEpisode),
+                None => return,
+            }
+        }
+    }
+
+    ///
+    /// Parse the alternative episode number, e.g. the absolute number of long-running shows.
+    /// It is the enclosed number right after the episode number.
+    /// e.g. "Title - 13 (01) [1080p]", "Title S02E01 (26)"
+    ///
+    fn parse_episode_alt(&mut self) {
+        if self.token_manager.has_token_with_metadata_kind(MetadataKind::EpisodeNumberAlt) {
+            return;
+        }
+
+        let tokens: Vec<&Token> = self.token_manager
+            .tokens()
+            .iter()
+            .filter(|t| !t.category.is_delimiter() && !t.has_metadata_kind(MetadataKind::ReleaseVersion))
+            .collect();
+
+        let episode_index = match tokens.iter().position(|t| t.has_metadata_kind(MetadataKind::EpisodeNumber)) {
+            Some(index) => index,
+            None => return,
+        };
+
+        if let [opening_bracket, number, closing_bracket, ..] = tokens[episode_index + 1..] {
+            if opening_bracket.category.is_opening_bracket()
+                && number.category.is_unknown()
+                && number.kind.is_numeric()
+                && closing_bracket.category.is_closing_bracket() {
+                self.token_manager.assign_metadata_kind(number.id, MetadataKind::EpisodeNumberAlt, Rule::EnclosedNumberAfterEpisode);
+            }
+        }
+    }
+
+    ///
+    /// Parse the year.
+    /// e.g. "Title (2019)", "Title [2023]", "Title (2019-2020)", "Show.2019.S01E01.1080p"
+    ///
+    fn parse_year(&mut self) {
+        if self.token_manager.has_token_with_metadata_kind(MetadataKind::Year) {
+            return;
+        }
+
+        let tokens = self.token_manager.tokens();
+
+        // Enclosed years, e.g. "(2019)" or "(2019-2020)"
+        for (index, token) in tokens.iter().enumerate() {
+            if !token.category.is_opening_bracket() {
+                continue;
+            }
+            let enclosed_tokens: Vec<&Token> = tokens
+                .iter()
+                .skip(index + 1)
+                .take_while(|t| !t.category.is_closing_bracket())
+                .filter(|t| !t.category.is_delimiter())
+                .collect();
+
+            let is_year = matches!(enclosed_tokens[..], [year] if year.kind.is_year());
+            let is_year_range = matches!(enclosed_tokens[..], [start, separator, end]
+                if start.kind.is_year() && separator.category.is_separator() && end.kind.is_year());
+
+            if is_year || is_year_range {
+                let year_ids: Vec<TokenId> = enclosed_tokens.iter().filter(|t| t.kind.is_year()).map(|t| t.id).collect();
+                for id in year_ids {
+                    self.token_manager.assign_metadata_kind(id, MetadataKind::Year, Rule::EnclosedYear);
+                }
+                return;
+            }
+        }
+
+        // Years that aren't enclosed, e.g. "Show.2019.S01E01.1080p".
+        // The year should follow the title and come before the metadata, "Title 2049 - 01" or "Title - 1999 [1080p]" are left as is.
+        // Start from the end since the title is more likely to contain numbers, e.g. "Blade.Runner.2049.2017.1080p"
+        let years: Vec<&Token> = tokens.iter().filter(|t| t.kind.is_year() && t.category.is_unknown() && !t.is_enclosed()).collect();
+        for token in years.iter().rev() {
+            if self.previous_token_is_unknown(token) && self.next_token_is_metadata_or_end(token) {
+                self.token_manager.assign_metadata_kind(token.id, MetadataKind::Year, Rule::YearBeforeMetadata);
+                return;
             }
         }
     }
 
+    ///
+    /// Parse the anime title.
+    /// The title is the first run of unknown tokens that are not enclosed (i.e. after the release group).
+    /// It ends before the first known token, keyword or bracket (e.g. "Season", "S01E04", "[480p]").
+    ///
+    fn parse_title(&mut self) {
+        if self.token_manager.has_token_with_metadata_kind(MetadataKind::Title) {
+            return;
+        }
+
+        let tokens = self.token_manager.tokens();
+        let start = match tokens.iter().position(|t| t.category.is_unknown() && !t.is_enclosed()) {
+            Some(start) => start,
+            None => return,
+        };
+
+        let mut title_ids: Vec<TokenId> = vec![];
+        let mut after_separator = false;
+        for token in tokens.iter().skip(start) {
+            match token.category {
+                // A low priority keyword ends the title when it is isolated by a separator,
+                // e.g. "Title - NCOP" but not "The Final Season"
+                TokenCategory::Unknown if after_separator && self.token_manager.identify_keyword(token).is_some() => break,
+                TokenCategory::Unknown if !token.is_enclosed() => {
+                    title_ids.push(token.id);
+                    after_separator = false;
+                }
+                // Delimiters and separators are kept when joining the title
+                TokenCategory::Delimiter => {}
+                TokenCategory::Separator => after_separator = true,
+                _ => break,
+            }
+        }
+
+        for id in title_ids {
+            self.token_manager.assign_metadata_kind(id, MetadataKind::Title, Rule::LeadingWords);
+        }
+    }
+
+    ///
+    /// Parse the episode title.
+    /// It is the run of unknown tokens that follows the episode number and ends before the first keyword or bracket.
+    /// e.g. "Title - 04 - The Labyrinth [480p]", "Show.S01E02.The.Beginning.1080p"
+    ///
+    fn parse_episode_title(&mut self) {
+        if self.token_manager.has_token_with_metadata_kind(MetadataKind::EpisodeTitle) {
+            return;
+        }
+
+        let tokens = self.token_manager.tokens();
+        let episode_index = match tokens.iter().rposition(|t| t.has_metadata_kind(MetadataKind::EpisodeNumber)) {
+            Some(index) => index,
+            None => return,
+        };
+
+        let mut episode_title_ids: Vec<TokenId> = vec![];
+        for token in tokens.iter().skip(episode_index + 1) {
+            // The version of the episode number comes before its title, e.g. "04v2 - The Labyrinth"
+            if token.has_metadata_kind(MetadataKind::ReleaseVersion) || token.category.is_keyword(KeywordCategory::ReleaseVersion) {
+                continue;
+            }
+            match token.category {
+                TokenCategory::Unknown if !token.is_enclosed() => episode_title_ids.push(token.id),
+                TokenCategory::Delimiter | TokenCategory::Separator => {}
+                _ => break,
+            }
+        }
+
+        for id in episode_title_ids {
+            self.token_manager.assign_metadata_kind(id, MetadataKind::EpisodeTitle, Rule::WordsAfterEpisode);
+        }
+    }
+
     ///
     /// Identify keywords for each token.
     /// When a keyword is found, update that token's category
     ///
     fn parse_keywords(&mut self, keyword_priority: KeywordPriority) {
-        let tokens = self.token_manager.get_tokens();
-        for token in tokens.iter() {
+        // Only the current token is updated, so the tokens stay in place
+        for index in 0..self.token_manager.tokens().len() {
+            let token = &self.token_manager.tokens()[index];
             // Only parse unknown tokens
-            if !token.category.is_known() {
+            if token.category.is_unknown() {
+                let id = token.id;
                 if let Some(ret_tokens) = self.token_manager.identify_keyword(token) {
+                    // Keywords of another priority are left for their own pass
+                    match ret_tokens[0].category.get_keyword() {
+                        Some(keyword) if keyword.priority == keyword_priority => {}
+                        _ => continue,
+                    }
                     match ret_tokens.len() {
                         1 => {
-                            let ret_token = ret_tokens[0].clone();
-                            match ret_token.category {
-                                TokenCategory::Keyword(keyword) => {
-                                    if keyword.priority == keyword_priority {
-                                        self.token_manager.update_token_category(token.uuid, TokenCategory::Keyword(keyword))
-                                    }
-                                }
-                                _ => {}
-                            }
+                            self.token_manager.update_token_category(id, ret_tokens[0].category.clone())
                         }
                         _ => {
-                            self.token_manager.update_token_category(token.uuid, TokenCategory::TokenParts(ret_tokens.clone()))
+                            self.token_manager.update_token_category(id, TokenCategory::TokenParts(ret_tokens))
                         }
-                        // _ => {}
                     }
                 }
             }
@@ -145,15 +637,10 @@ impl Parser {
     /// Flatten tokens whose category is TokenCategory::TokenParts.
     ///
     fn normalize_keywords(&mut self) {
-        let tokens = self.token_manager.get_tokens();
-        let iterator = tokens.iter();
-        for token in iterator.filter(|t| t.category.is_token_parts()) {
-            // we don't skip delimiters to get real index
-            if let Some(token_index) = self.token_manager.get_index_of_token(token, false) {
-                if let Some(token_parts) = token.category.get_token_parts() {
-                    self.token_manager.flatten_token_at(token_index, token_parts)
-                }
-            }
+        // we don't skip delimiters to get real index
+        while let Some(token_index) = self.token_manager.tokens().iter().position(|t| t.category.is_token_parts()) {
+            let token_parts = self.token_manager.tokens()[token_index].category.get_token_parts().unwrap_or_default();
+            self.token_manager.flatten_token_at(token_index, token_parts)
         }
     }
 
@@ -161,69 +648,164 @@ impl Parser {
     /// Parse FileChecksum and VideoResolution tokens
     ///
     fn parse_file_checksum_and_video_resolution(&mut self) {
-        let tokens = self.token_manager.get_tokens();
-        for token in tokens.iter() {
-            if !token.category.is_known() && is_crc32(token.value.as_str()) {
-                self.token_manager.update_token_category(token.uuid, TokenCategory::Known(MetadataKind::FileChecksum))
+        for index in 0..self.token_manager.tokens().len() {
+            let token = &self.token_manager.tokens()[index];
+            let id = token.id;
+            let is_checksum = !token.category.is_known() && is_crc32(token.value.as_str());
+            let is_resolution = is_video_resolution(token.value.as_str());
+            if is_checksum {
+                self.token_manager.assign_metadata_kind(id, MetadataKind::FileChecksum, Rule::Checksum)
             }
-            if is_video_resolution(token.value.as_str()) {
-                self.token_manager.update_token_category(token.uuid, TokenCategory::Known(MetadataKind::VideoResolution))
+            if is_resolution {
+                self.token_manager.assign_metadata_kind(id, MetadataKind::VideoResolution, Rule::ResolutionFormat)
             }
         }
     }
 
     /// Get a potential number with decimal, do not ignore delimiters.
-    /// Tokens should be of TokenKind::Number.
+    /// Tokens should be of TokenKind::Number or TokenKind::Year.
     /// e.g. "1" "." "5"
-    fn get_number_with_decimal_after(&mut self, token: &Token) -> Option<Vec<Token>> {
+    fn get_number_with_decimal_after(&self, token: &Token) -> Option<Vec<Token>> {
         if let Some(index) = self.token_manager.get_index_of_token(token, false) {
             if let Some(tokens) = self.token_manager.get_matching_tokens_after(index, vec![TokenCategory::Unknown, TokenCategory::Delimiter, TokenCategory::Unknown], false) {
-                if tokens.len() != 3 {
+                // The decimal part can have a version, e.g. "07.5v2"
+                let is_decimal_part = |t: &Token| t.kind.is_numeric() || split_episode_number(t.value.as_str()).is_some();
+                if tokens.len() != 3 || !tokens[0].kind.is_numeric() || !is_decimal_part(&tokens[2]) {
                     return None;
                 }
                 if let Some(delimiter) = tokens.iter().find(|t| t.category.is_delimiter()) {
-                    if delimiter.value == ".".to_string() {
+                    if delimiter.value == "." {
                         return Some(tokens);
                     }
                 }
             }
         }
-        return None;
+        None
+    }
+
+    /// Get a potential number with decimal starting at the token.
+    /// e.g. "07" "." "5"
+    fn get_decimal_number_at(&self, token: &Token) -> Option<Vec<Token>> {
+        let index = self.token_manager.get_index_of_token(token, false)?;
+        let previous_token = self.token_manager.get_token_before(index, false)?;
+        self.get_number_with_decimal_after(&previous_token)
     }
 
     /// Get a potential number range, ignore delimiters.
-    /// Tokens should be of TokenKind::Number.
+    /// Tokens should be of TokenKind::Number or TokenKind::Year.
     /// e.g. "1" (delimiter)? "-" (delimiter)? "3"
-    fn get_number_range_after(&mut self, token: &Token) -> Option<Vec<Token>> {
+    fn get_number_range_after(&self, token: &Token) -> Option<Vec<Token>> {
         if let Some(index) = self.token_manager.get_index_of_token(token, true) {
             if let Some(tokens) = self.token_manager.get_matching_tokens_after(index, vec![TokenCategory::Unknown, TokenCategory::Separator, TokenCategory::Unknown], true) {
                 if tokens.len() != 3 {
                     return None;
                 }
-                let number_tokens: Vec<Token> = tokens.iter().filter(|t| t.kind.is_number()).cloned().collect();
+                let number_tokens: Vec<Token> = tokens.iter().filter(|t| t.kind.is_numeric()).cloned().collect();
                 if number_tokens.len() != 2 {
                     return None;
                 }
                 return Some(number_tokens);
             }
         }
-        return None;
+        None
     }
 
     /// Get a potential number
-    fn get_number_or_like_after(&mut self, token: &Token) -> Option<Token> {
+    fn get_number_or_like_after(&self, token: &Token) -> Option<Token> {
         if let Some(index) = self.token_manager.get_index_of_token(token, true) {
             if let Some(token) = self.token_manager.get_token_after(index, true) {
-                if token.kind.is_number_or_like() {
+                if token.kind.is_numeric_or_like() {
                     return Some(token);
                 }
             }
         }
-        return None;
+        None
+    }
+
+    ///
+    /// The unknown number split from a combined prefix, e.g. "S", "02"
+    ///
+    fn get_number_right_after(&self, token: &Token) -> Option<Token> {
+        let index = self.token_manager.get_index_of_token(token, false)?;
+        let token = self.token_manager.get_token_after(index, false)?;
+        if token.category.is_unknown() && token.kind.is_numeric() {
+            return Some(token);
+        }
+        None
+    }
+
+    ///
+    /// The end of a season range is prefixed as well, e.g. "S1-S3".
+    /// Without the prefix it is an episode number, e.g. "S2 - 05".
+    ///
+    fn get_season_range_end_after(&self, token: &Token) -> Option<Token> {
+        let index = self.token_manager.get_index_of_token(token, true)?;
+        let separator = self.token_manager.get_token_after(index, true)?;
+        if !is_range_separator(&separator) {
+            return None;
+        }
+        let prefix = self.token_manager.get_token_after(index + 1, true)?;
+        if !prefix.category.is_keyword(KeywordCategory::SeasonPrefix) {
+            return None;
+        }
+        self.get_number_right_after(&prefix)
+    }
+
+    fn get_token_before(&self, token: &Token) -> Option<Token> {
+        let index = self.token_manager.get_index_of_token(token, true)?;
+        self.token_manager.get_token_before(index, true)
+    }
+
+    fn previous_token_is_separator(&self, token: &Token) -> bool {
+        if let Some(index) = self.token_manager.get_index_of_token(token, true) {
+            if let Some(prev_token) = self.token_manager.get_token_before(index, true) {
+                return prev_token.category.is_separator();
+            }
+        }
+        false
+    }
+
+    fn previous_token_is_unknown(&self, token: &Token) -> bool {
+        if let Some(index) = self.token_manager.get_index_of_token(token, true) {
+            if let Some(prev_token) = self.token_manager.get_token_before(index, true) {
+                return prev_token.category.is_unknown();
+            }
+        }
+        false
+    }
+
+    /// Low priority keywords (e.g. "END") are not considered unknown since they will be identified later.
+    fn next_token_is_unknown(&self, token: &Token) -> bool {
+        if let Some(index) = self.token_manager.get_index_of_token(token, true) {
+            if let Some(next_token) = self.token_manager.get_token_after(index, true) {
+                return next_token.category.is_unknown() && self.token_manager.identify_keyword(&next_token).is_none();
+            }
+        }
+        false
+    }
+
+    fn next_token_is_bracket_or_end(&self, token: &Token) -> bool {
+        if let Some(index) = self.token_manager.get_index_of_token(token, true) {
+            return match self.token_manager.get_token_after(index, true) {
+                Some(next_token) => next_token.category.is_bracket(),
+                None => true,
+            };
+        }
+        false
+    }
+
+    fn next_token_is_metadata_or_end(&self, token: &Token) -> bool {
+        if let Some(index) = self.token_manager.get_index_of_token(token, true) {
+            return match self.token_manager.get_token_after(index, true) {
+                Some(next_token) => next_token.category.is_bracket() || next_token.category.is_any_keyword() || next_token.category.is_known(),
+                None => true,
+            };
+        }
+        false
     }
 
     /// Get a potential number
-    fn get_delimiter_before(&mut self, token: &Token) -> Option<Token> {
+    fn get_delimiter_before(&self, token: &Token) -> Option<Token> {
         if let Some(index) = self.token_manager.get_index_of_token(token, false) {
             if let Some(token) = self.token_manager.get_token_before(index, false) {
                 if token.category.is_delimiter() {
@@ -231,7 +813,16 @@ impl Parser {
                 }
             }
         }
-        return None;
+        None
+    }
+}
+
+/// e.g. "-", "~", "to"
+fn is_range_separator(token: &Token) -> bool {
+    match token.category {
+        TokenCategory::Separator => token.value == "-" || token.value == "~",
+        TokenCategory::Unknown => token.value.to_lowercase() == "to",
+        _ => false,
     }
 }
 
@@ -242,8 +833,7 @@ fn test_parsing() {
     let mut parser = Parser::new(tokens);
     parser.parse();
 
-    assert!(true);
-    println!("{:#?}", parser.token_manager.get_tokens());
+    println!("{:#?}", parser.token_manager.tokens());
 }
 
 #[test]
@@ -253,8 +843,7 @@ fn test_parsing_00() {
     let mut parser = Parser::new(tokens);
     parser.parse();
 
-    assert!(true);
-    println!("{:#?}", parser.token_manager.get_tokens());
+    println!("{:#?}", parser.token_manager.tokens());
 }
 
 #[test]
@@ -264,8 +853,7 @@ fn test_parsing_01() {
     let mut parser = Parser::new(tokens);
     parser.parse();
 
-    assert!(true);
-    println!("{:#?}", parser.token_manager.get_tokens());
+    println!("{:#?}", parser.token_manager.tokens());
 }
 
 #[test]
@@ -275,8 +863,7 @@ fn test_parsing_02() {
     let mut parser = Parser::new(tokens);
     parser.parse();
 
-    assert!(true);
-    println!("{:#?}", parser.token_manager.get_tokens());
+    println!("{:#?}", parser.token_manager.tokens());
 }
 
 #[test]
@@ -286,6 +873,363 @@ fn test_parsing_03() {
     let mut parser = Parser::new(tokens);
     parser.parse();
 
-    assert!(true);
-    println!("{:#?}", parser.token_manager.get_tokens());
-}
\ No newline at end of file
+    println!("{:#?}", parser.token_manager.tokens());
+}
+
+#[test]
+fn test_parse_title() {
+    assert_eq!(Some(String::from("Tower of Druaga - Sword of Uruk")), crate::parse("[HorribleSubs] Tower of Druaga - Sword of Uruk - S01E04 [480p]").title);
+    assert_eq!(Some(String::from("Tower of Druaga - Sword of Uruk")), crate::parse("[HorribleSubs]_Tower_of_Druaga_-_Sword_of_Uruk_-_S01E04_[480p]").title);
+    assert_eq!(Some(String::from("Jujutsu Kaisen")), crate::parse("[SubsPlease] Jujutsu Kaisen Season 2 - 01 [1080p]").title);
+    assert_eq!(Some(String::from("Jujutsu Kaisen")), crate::parse("[SubsPlease] Jujutsu Kaisen Seasons 01 - 03 [1080p]").title);
+    assert_eq!(Some(String::from("Tsubasa Chronicle")), crate::parse("[Group] Tsubasa Chronicle S01E02 [720p]").title);
+}
+
+#[test]
+fn test_parse_episode() {
+    test_parse_episode_number("[HorribleSubs] Tower of Druaga - Sword of Uruk - 04 [480p]", "04");
+    test_parse_episode_number("[ST]_Kemono_no_Souja_Erin_-_12_(1280x720_h264)_[0F5F884F]", "12");
+    test_parse_episode_number("[Group]_Kemono_no_Souja_Erin_12_[720p]", "12");
+    test_parse_episode_number("[Group] Title - 04v2 [1080p]", "04");
+    test_parse_episode_number("[Group] Title - 04' [1080p]", "04");
+    test_parse_episode_number("[Group] 86 - Eighty Six - 01 [1080p]", "01");
+    test_parse_episode_number("[Group] Mob Psycho 100 - 03 - Episode Title [1080p]", "03");
+    test_parse_episode_number("05", "05");
+
+    let parsed = crate::parse("[Group] Title - 04 [1080p]");
+    assert_eq!(Some(String::from("Title")), parsed.title);
+}
+
+#[test]
+fn test_parse_episode_title_number() {
+    assert!(crate::parse("[Group] 86 [1080p]").episode_numbers.is_empty());
+    assert!(crate::parse("[Group] Title - Part One [1080p]").episode_numbers.is_empty());
+}
+
+fn test_parse_episode_number(input: &str, expected: &str) {
+    let parsed = crate::parse(input);
+    println!("{:#?}", parsed);
+    assert_eq!(vec![expected], parsed.episode_numbers);
+}
+
+#[test]
+fn test_parse_release_group() {
+    assert_eq!(Some(String::from("Erai-raws")), crate::parse("[Erai-raws] Title - 04 [1080p]").release_group);
+    assert_eq!(Some(String::from("ST")), crate::parse("[ST]_Kemono_no_Souja_Erin_-_12_(1280x720_h264)_[0F5F884F].mkv").release_group);
+    assert_eq!(Some(String::from("HorribleSubs")), crate::parse("[HorribleSubs] Tower of Druaga - Sword of Uruk - 04 [480p]").release_group);
+    assert_eq!(Some(String::from("EMBER")), crate::parse("Violet.Evergarden.The.Movie.1080p.Dual.Audio.BDRip.10.bits.DD.x265-EMBER").release_group);
+    assert_eq!(Some(String::from("Title")), crate::parse("[Erai-raws] Title - 04 [1080p]").title);
+}
+
+#[test]
+fn test_parse_release_group_none() {
+    assert_eq!(None, crate::parse("[1080p] Title - 04").release_group);
+    assert_eq!(None, crate::parse("Title - 04 [Group]").release_group);
+    assert_eq!(None, crate::parse("Title.S01E01.1080p.Spider-Man").release_group);
+    assert_eq!(None, crate::parse("[v2] Title - 05 [1080p]").release_group);
+    assert_eq!(None, crate::parse("[END] Title - 05 [1080p]").release_group);
+}
+
+#[test]
+fn test_parse_episode_title() {
+    let parsed = crate::parse("[HorribleSubs] Tower of Druaga - Sword of Uruk - 04 - The Labyrinth [480p]");
+    assert_eq!(Some(String::from("Tower of Druaga - Sword of Uruk")), parsed.title);
+    assert_eq!(Some(String::from("The Labyrinth")), parsed.episode_title);
+
+    let parsed = crate::parse("Show.S01E02.The.Beginning.1080p.WEB");
+    assert_eq!(Some(String::from("Show")), parsed.title);
+    assert_eq!(Some(String::from("The Beginning")), parsed.episode_title);
+
+    let parsed = crate::parse("[Group] Title - 04v2 - The Labyrinth [480p]");
+    assert_eq!(Some(String::from("The Labyrinth")), parsed.episode_title);
+    assert_eq!(Some(2), parsed.release_version);
+    assert_eq!(Some(String::from("The Beginning")), crate::parse("Show.S01E02v2.The.Beginning.1080p.WEB").episode_title);
+
+    assert_eq!(None, crate::parse("[Group] Title - 04v2 [1080p]").episode_title);
+    assert_eq!(None, crate::parse("[Group] Title - 04 [1080p]").episode_title);
+}
+
+#[test]
+fn test_parse_year() {
+    assert_eq!(vec![String::from("2019")], crate::parse("[Group] Title (2019) - 04 [1080p]").years);
+    assert_eq!(vec![String::from("2023")], crate::parse("[Group] Title [2023] - 04 [1080p]").years);
+    assert_eq!(vec![String::from("2019"), String::from("2020")], crate::parse("[Group] Title (2019-2020) [BD 1080p]").years);
+    assert_eq!(vec![String::from("2019")], crate::parse("Show.2019.S01E01.1080p.WEB").years);
+    assert_eq!(vec![String::from("2017")], crate::parse("Blade.Runner.2049.2017.1080p.BluRay").years);
+
+    let parsed = crate::parse("[Group] Title (2019) - 04 [1080p]");
+    assert_eq!(Some(String::from("Title")), parsed.title);
+    assert_eq!(vec!["04"], parsed.episode_numbers);
+}
+
+#[test]
+fn test_parse_year_none() {
+    let parsed = crate::parse("[Group] Title 2049 - 01 [1080p]");
+    assert!(parsed.years.is_empty());
+    assert_eq!(Some(String::from("Title 2049")), parsed.title);
+
+    let parsed = crate::parse("[Group] One Piece - 1999 [1080p]");
+    assert!(parsed.years.is_empty());
+    assert_eq!(vec!["1999"], parsed.episode_numbers);
+
+    assert_eq!(Some(String::from("Blade Runner 2049")), crate::parse("Blade.Runner.2049.2017.1080p.BluRay").title);
+}
+
+#[test]
+fn test_parse_year_episode_number() {
+    let parsed = crate::parse("[Group] Title Episode 1999 [1080p]");
+    assert!(parsed.years.is_empty());
+    assert_eq!(vec!["1999"], parsed.episode_numbers);
+    assert_eq!(Some(String::from("Title")), parsed.title);
+
+    let parsed = crate::parse("[Group] Title - 1998-2000 [1080p]");
+    assert!(parsed.years.is_empty());
+    assert_eq!(vec!["1998", "2000"], parsed.episode_numbers);
+}
+
+#[test]
+fn test_parse_low_priority_keywords() {
+    let parsed = crate::parse("[Group] Title - 12 END [1080p]");
+    assert_eq!(vec![String::from("END")], parsed.release_information);
+    assert_eq!(vec!["12"], parsed.episode_numbers);
+    assert_eq!(None, parsed.episode_title);
+
+    let parsed = crate::parse("[Group] Final Fantasy - 01 [1080p]");
+    assert_eq!(Some(String::from("Final Fantasy")), parsed.title);
+    assert!(parsed.release_information.is_empty());
+
+    let parsed = crate::parse("[Group] Shingeki no Kyojin The Final Season - 01 [1080p]");
+    assert_eq!(Some(String::from("Shingeki no Kyojin The Final Season")), parsed.title);
+    assert!(parsed.release_information.is_empty());
+
+    let parsed = crate::parse("[Group] Title - NCOP [1080p][ITA]");
+    assert_eq!(vec![String::from("NCOP")], parsed.anime_types);
+    assert_eq!(vec![String::from("ITA")], parsed.languages);
+}
+
+#[test]
+fn test_parse_volume_and_part() {
+    let parsed = crate::parse("[Group] Title Vol.3 [BD 1080p]");
+    assert_eq!(vec![String::from("3")], parsed.volume_numbers);
+    assert_eq!(Some(String::from("Title")), parsed.title);
+    assert!(parsed.episode_numbers.is_empty());
+
+    assert_eq!(vec![String::from("3")], crate::parse("[Group] Title Vol3 [BD 1080p]").volume_numbers);
+    assert_eq!(vec![String::from("01"), String::from("04")], crate::parse("[Group] Title Volume 01-04 [BD 1080p]").volume_numbers);
+
+    let parsed = crate::parse("[Group] Title Part 2 - 05 [1080p]");
+    assert_eq!(vec![String::from("2")], parsed.parts);
+    assert_eq!(vec!["05"], parsed.episode_numbers);
+    assert_eq!(Some(String::from("Title")), parsed.title);
+
+    assert_eq!(vec![String::from("2")], crate::parse("[Group] Title Cour 2 - 05 [1080p]").parts);
+    assert_eq!(vec![String::from("1"), String::from("2")], crate::parse("[Group] Title Parts 1 - 2 [1080p]").parts);
+}
+
+#[test]
+fn test_parse_episode_range() {
+    test_parse_episode_range_of("[Group] Title (01-12) [BD]", "01", "12");
+    test_parse_episode_range_of("[Group] Title (01 - 12) [BD]", "01", "12");
+    test_parse_episode_range_of("[Group] Title 01~13 [BD]", "01", "13");
+    test_parse_episode_range_of("[Group] Title 01 to 24 [BD]", "01", "24");
+    test_parse_episode_range_of("[Group] Title E01-E03 [BD]", "01", "03");
+    test_parse_episode_range_of("Title.S01E01-E03.1080p", "01", "03");
+
+    let parsed = crate::parse("[Group] Title 01 to 24 [BD]");
+    assert_eq!(Some(String::from("Title")), parsed.title);
+}
+
+#[test]
+fn test_parse_episode_range_none() {
+    let parsed = crate::parse("[Group] Title 2 - 05 [1080p]");
+    assert_eq!(None, parsed.episode_range);
+    assert_eq!(vec!["05"], parsed.episode_numbers);
+
+    assert_eq!(None, crate::parse("[Group] Title - 05 [1080p]").episode_range);
+}
+
+fn test_parse_episode_range_of(input: &str, start: &str, end: &str) {
+    let parsed = crate::parse(input);
+    println!("{:#?}", parsed);
+    let episode_range = parsed.episode_range.unwrap();
+    assert_eq!(start, episode_range.start);
+    assert_eq!(end, episode_range.end);
+}
+
+#[test]
+fn test_parse_decimal_episode() {
+    let parsed = crate::parse("[Group] Title - 07.5 [720p]");
+    println!("{:#?}", parsed);
+    assert_eq!(vec!["07.5"], parsed.episode_numbers);
+    assert_eq!(7.5, parsed.episode_numbers[0].number);
+    assert_eq!(Some(String::from("Title")), parsed.title);
+
+    assert_eq!(vec!["12.5"], crate::parse("[Group]_Title_-_12.5_[720p]").episode_numbers);
+    assert_eq!(vec!["12"], crate::parse("[Group] Title - 12 [720p]").episode_numbers);
+
+    let parsed = crate::parse("[Group] Title - 07.5v2 [720p]");
+    assert_eq!(vec!["07.5"], parsed.episode_numbers);
+    assert_eq!(Some(2), parsed.release_version);
+    assert_eq!(Some(String::from("Title")), parsed.title);
+    assert_eq!(Some("v2"), parsed.span_of(MetadataKind::ReleaseVersion).and_then(|s| s.slice_of("[Group] Title - 07.5v2 [720p]")));
+}
+
+#[test]
+fn test_parse_release_version() {
+    let parsed = crate::parse("[Group] Title - 04v2 [1080p]");
+    assert_eq!(vec!["04"], parsed.episode_numbers);
+    assert_eq!(Some(2), parsed.release_version);
+
+    assert_eq!(Some(2), crate::parse("[Group] Title - 04' [1080p]").release_version);
+    assert_eq!(Some(3), crate::parse("Title.S03E03v3.1080p").release_version);
+    assert_eq!(Some(2), crate::parse("[Group] Title - 04 [v2][1080p]").release_version);
+    assert_eq!(None, crate::parse("[Group] Title - 04 [1080p]").release_version);
+}
+
+#[test]
+fn test_parse_video_resolution() {
+    let parsed = crate::parse("[Group] Title - 04 [1080i]");
+    assert_eq!(Some(String::from("1080i")), parsed.video_resolution.map(|r| r.to_string()));
+
+    let parsed = crate::parse("[Group] Title - 04 [4K]");
+    assert_eq!(Some(2160), parsed.video_resolution.map(|r| r.normalized_height()));
+    assert!(parsed.video_terms.is_empty());
+
+    let parsed = crate::parse("[ST]_Kemono_no_Souja_Erin_-_12_(1280x720_h264)_[0F5F884F]");
+    assert_eq!(Some(720), parsed.video_resolution.map(|r| r.normalized_height()));
+}
+
+#[test]
+fn test_parse_episode_alt() {
+    let parsed = crate::parse("[Group] Title - 13 (01) [1080p]");
+    assert_eq!(vec!["13"], parsed.episode_numbers);
+    assert_eq!(vec!["01"], parsed.episode_numbers_alt);
+    assert!(parsed.has_episode_number(13.0));
+    assert!(parsed.has_episode_number(1.0));
+
+    let parsed = crate::parse("Title S02E01 (26) [1080p]");
+    assert_eq!(vec!["01"], parsed.episode_numbers);
+    assert_eq!(vec!["26"], parsed.episode_numbers_alt);
+
+    assert_eq!(vec!["26"], crate::parse("[Group] Title - 01v2 (26) [1080p]").episode_numbers_alt);
+    assert!(crate::parse("[Group] Title - 13 (1080p)").episode_numbers_alt.is_empty());
+}
+
+#[test]
+fn test_parse_ordinal_season() {
+    let parsed = crate::parse("[Group] Title 4th Season - 03 [1080p]");
+    assert_eq!(vec!["4".to_string()], parsed.seasons);
+    assert_eq!(vec!["03"], parsed.episode_numbers);
+    assert_eq!(Some("Title".to_string()), parsed.title);
+
+    assert_eq!(vec!["2".to_string()], crate::parse("Title 2nd Season - 01").seasons);
+    assert_eq!(vec!["1".to_string()], crate::parse("Title 1st Season - 01").seasons);
+    assert_eq!(vec!["3".to_string()], crate::parse("Title 3rd Season - 01").seasons);
+    assert_eq!(vec!["21".to_string()], crate::parse("Title 21st Season - 01").seasons);
+
+    // After the prefix
+    let parsed = crate::parse("Title Season 2nd - 03");
+    assert_eq!(vec!["2".to_string()], parsed.seasons);
+    assert_eq!(vec!["03"], parsed.episode_numbers);
+}
+
+#[test]
+fn test_parse_combined_season() {
+    let parsed = crate::parse("[Group] Title S2 - 05 [1080p]");
+    assert_eq!(vec!["2".to_string()], parsed.seasons);
+    assert_eq!(vec!["05"], parsed.episode_numbers);
+    assert_eq!(Some("Title".to_string()), parsed.title);
+
+    let parsed = crate::parse("[Group] Title S02 [Batch]");
+    assert_eq!(vec!["02".to_string()], parsed.seasons);
+    assert!(parsed.episode_numbers.is_empty());
+
+    let parsed = crate::parse("Title S1-S3 [1080p]");
+    assert_eq!(vec!["1".to_string(), "3".to_string()], parsed.seasons);
+    assert!(parsed.episode_numbers.is_empty());
+
+    assert_eq!(vec!["01".to_string(), "03".to_string()], crate::parse("Title S01 - S03 Complete").seasons);
+}
+
+#[test]
+fn test_parse_multi_episode() {
+    let parsed = crate::parse("[Group] Title S01E01E02 [1080p]");
+    assert_eq!(vec!["01".to_string()], parsed.seasons);
+    assert_eq!(vec!["01", "02"], parsed.episode_numbers);
+    assert_eq!(None, parsed.episode_range);
+    assert_eq!(Some("Title".to_string()), parsed.title);
+
+    let parsed = crate::parse("[Group] Title E01+E02 [1080p]");
+    assert_eq!(vec!["01", "02"], parsed.episode_numbers);
+    assert_eq!(Some("Title".to_string()), parsed.title);
+
+    let parsed = crate::parse("[Group] Title - 01+02+03 [1080p]");
+    assert_eq!(vec!["01", "02", "03"], parsed.episode_numbers);
+    assert_eq!(Some("Title".to_string()), parsed.title);
+
+    let parsed = crate::parse("[Group] Title S01E01-E02 [1080p]");
+    assert_eq!(vec!["01", "02"], parsed.episode_numbers);
+    assert!(parsed.episode_range.is_some());
+
+    assert_eq!(vec!["05"], crate::parse("[Group] Title E05 [1080p]").episode_numbers);
+}
+
+#[test]
+fn test_parse_cross_season_episodes() {
+    let parsed = crate::parse("[Group] Title S01E12-S02E01 [1080p]");
+    assert_eq!(vec!["01".to_string(), "02".to_string()], parsed.seasons);
+    assert_eq!(vec!["12", "01"], parsed.episode_numbers);
+
+    let range = parsed.episode_range.unwrap();
+    assert_eq!((Some("01"), "12"), (range.start_season.as_deref(), range.start.text.as_str()));
+    assert_eq!((Some("02"), "01"), (range.end_season.as_deref(), range.end.text.as_str()));
+}
+
+#[test]
+fn test_parse_season_confidence() {
+    let parsed = crate::parse("[Group] Title Season 1 - 12 [1080p]");
+    assert_eq!(vec!["1".to_string()], parsed.seasons);
+    assert_eq!(vec!["12"], parsed.episode_numbers);
+    assert_eq!(Some(Rule::SeasonEpisodeLargeNumber), parsed.confidence_of(MetadataKind::Season).map(|c| c.rule));
+
+    let parsed = crate::parse("[Group] Title Season 2 [1080p]");
+    assert_eq!(Some(Rule::Prefix), parsed.confidence_of(MetadataKind::Season).map(|c| c.rule));
+
+    let parsed = crate::parse("[Group] Title - 04 [1080p]");
+    assert_eq!(Some(Rule::NumberAfterSeparator), parsed.confidence_of(MetadataKind::EpisodeNumber).map(|c| c.rule));
+    assert_eq!(Some(Rule::LeadingWords), parsed.confidence_of(MetadataKind::Title).map(|c| c.rule));
+}
+
+#[test]
+fn test_parse_season_episode_large_number() {
+    // The episode number shouldn't fall through to the "1 - 5" rule
+    let parsed = crate::parse("[Group] Title Season 1 - 12 [1080p]");
+    assert_eq!(vec!["1".to_string()], parsed.seasons);
+    assert_eq!(vec!["12"], parsed.episode_numbers);
+}
+
+#[test]
+fn test_parse_deterministic() {
+    let parse_tokens = || {
+        let mut parser = Parser::new(tokenizer::tokenize("[Group] Title S01E01E02v2 - 07.5 [1080p]"));
+        parser.parse();
+        parser.token_manager.tokens().to_vec()
+    };
+
+    assert_eq!(parse_tokens(), parse_tokens());
+}
+
+#[test]
+fn test_parse_low_priority_combined_keyword() {
+    let mut keyword_manager = KeywordManager::new();
+    keyword_manager.add_group(
+        KeywordCategory::AnimeType,
+        KeywordKind::CombinedOrSeparated { next_token_kind: TokenKind::NumberLike },
+        KeywordPriority::Low,
+        vec!["PV"],
+    );
+
+    let parsed = crate::parse_with_keyword_manager("[Group] Title - PV2 [1080p]", &keyword_manager);
+    assert_eq!(vec![String::from("PV")], parsed.anime_types);
+    assert_eq!(Some(String::from("Title")), parsed.title);
+}
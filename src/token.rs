@@ -2,18 +2,24 @@
 
 use std::fmt::Display;
 
-use uuid::Uuid;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
+use crate::confidence::Rule;
 use crate::keyword::{Keyword, KeywordCategory};
 use crate::metadata::MetadataKind;
 
 #[derive(Debug, Eq, PartialEq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum BracketType {
     Opening, // e.g. [ ( {
     Closing, // e.g. ] ) }
 }
 
 #[derive(Debug, Eq, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum TokenCategory {
     Bracket(BracketType), // e.g. [ ] ( ) { }
     Delimiter, // e.g. ' ', '_', '.'
@@ -25,44 +31,81 @@ pub enum TokenCategory {
 }
 
 #[derive(Debug, Eq, PartialEq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum TokenKind {
     Unknown,
     SingleCharacter,
     String,
     Number, // "00", "01"
     NumberLike, // "01v2" "01'" "01x05" "01.5", "1st",
-    Year,
+    Year, // "2019"
 }
 
+///
+/// Byte offsets of a token or field in the parsed filename, e.g. "S01E04" -> 0..6
+///
+#[derive(Debug, Default, Eq, PartialEq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+///
+/// Identifies a token in its TokenManager, even after the tokens around it were split or fused.
+/// Ids follow the order of the tokenized input, new tokens get the next free id.
+///
+#[derive(Debug, Default, Eq, PartialEq, Clone, Copy, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TokenId(pub usize);
+
 #[derive(Debug, Eq, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Token {
-    pub uuid: Uuid,
+    pub id: TokenId,
     pub value: String,
     pub enclosed: bool,
     pub category: TokenCategory,
-    pub kind: TokenKind
+    pub kind: TokenKind,
+    pub rule: Option<Rule>, // The parser rule that identified a Known token
+    pub span: Span,
 }
 
 impl Token {
     pub fn new<T: Display>(value: T, category: TokenCategory) -> Token {
         Token {
-            uuid: Uuid::new_v4(),
+            id: TokenId::default(),
             value: value.to_string(),
             enclosed: false,
             category,
             kind: TokenKind::Unknown,
+            rule: None,
+            span: Span::default(),
         }
     }
     pub fn new_with_kind<T: Display>(value: T, category: TokenCategory, kind: TokenKind) -> Token {
         Token {
-            uuid: Uuid::new_v4(),
+            id: TokenId::default(),
             value: value.to_string(),
             enclosed: false,
             category,
             kind,
+            rule: None,
+            span: Span::default(),
         }
     }
 
+    pub fn with_span(mut self, start: usize, end: usize) -> Token {
+        self.span = Span { start, end };
+        self
+    }
+
+    pub fn with_rule(mut self, rule: Rule) -> Token {
+        self.rule = Some(rule);
+        self
+    }
+
     pub fn is_enclosed(&self) -> bool {
         self.enclosed
     }
@@ -79,6 +122,46 @@ impl Token {
     }
 }
 
+impl Span {
+    ///
+    /// Span from the start of the first span to the end of the last one.
+    ///
+    pub fn join(first: Span, last: Span) -> Span {
+        Span { start: first.start, end: last.end }
+    }
+
+    ///
+    /// The matched part of the filename, e.g. to highlight it.
+    ///
+    pub fn slice_of<'a>(&self, input: &'a str) -> Option<&'a str> {
+        input.get(self.start..self.end)
+    }
+
+    ///
+    /// Same span in a longer input, e.g. a file name at 7 in "/Anime/Title - 01.mkv".
+    ///
+    pub fn offset_by(&self, offset: usize) -> Span {
+        Span { start: self.start + offset, end: self.end + offset }
+    }
+}
+
+///
+/// Gives consecutive spans to the parts of a split token, e.g. "S01E04" at 10..16 ->
+/// "S" 10..11, "01" 11..13, "E" 13..14, "04" 14..16
+/// Parts that don't add up to the token, e.g. a normalized value, all get its whole span.
+///
+pub fn set_consecutive_spans(tokens: &mut [Token], span: Span) {
+    let length: usize = tokens.iter().map(|t| t.value.len()).sum();
+    let mut start = span.start;
+    for token in tokens.iter_mut() {
+        token.span = if length == span.end - span.start {
+            Span { start, end: start + token.value.len() }
+        } else {
+            span
+        };
+        start = token.span.end;
+    }
+}
 
 //------
 
@@ -93,33 +176,21 @@ impl TokenCategory {
     }
 
     pub fn is_bracket(&self) -> bool {
-        match self {
-            TokenCategory::Bracket(_) => true,
-            _ => false
-        }
+        matches!(self, TokenCategory::Bracket(_))
     }
     pub fn is_delimiter(&self) -> bool {
-        match self {
-            TokenCategory::Delimiter => true,
-            _ => false
-        }
+        matches!(self, TokenCategory::Delimiter)
     }
     pub fn is_separator(&self) -> bool {
-        match self {
-            TokenCategory::Separator => true,
-            _ => false
-        }
+        matches!(self, TokenCategory::Separator)
     }
     pub fn is_any_keyword(&self) -> bool {
-        match self {
-            TokenCategory::Keyword(_) => true,
-            _ => false
-        }
+        matches!(self, TokenCategory::Keyword(_))
     }
     pub fn is_keyword(&self, keyword_category: KeywordCategory) -> bool {
         match self {
             TokenCategory::Keyword(keyword) => {
-                return keyword.category == keyword_category;
+                keyword.category == keyword_category
             },
             _ => false
         }
@@ -127,85 +198,60 @@ impl TokenCategory {
     pub fn get_keyword(&self) -> Option<Keyword> {
         match self {
             TokenCategory::Keyword(keyword) => {
-                return Some(keyword.clone())
+                Some(keyword.clone())
             },
             _ => None
         }
     }
     pub fn is_token_parts(&self) -> bool {
-        match self {
-            TokenCategory::TokenParts(_) => true,
-            _ => false
-        }
+        matches!(self, TokenCategory::TokenParts(_))
     }
     pub fn get_token_parts(&self) -> Option<Vec<Token>> {
         match self {
             TokenCategory::TokenParts(tokens) => {
-                return Some(tokens.clone())
+                Some(tokens.clone())
             },
             _ => None
         }
     }
     pub fn is_unknown(&self) -> bool {
-        match self {
-            TokenCategory::Unknown => true,
-            _ => false
-        }
+        matches!(self, TokenCategory::Unknown)
     }
     pub fn is_known(&self) -> bool {
-        match self {
-            TokenCategory::Known(_) => true,
-            _ => false
-        }
+        matches!(self, TokenCategory::Known(_))
     }
     pub fn is_opening_bracket(&self) -> bool {
-        match self {
-            TokenCategory::Bracket(b_type) => {
-                return *b_type == BracketType::Opening;
-            },
-            _ => false
-        }
+        matches!(self, TokenCategory::Bracket(b_type) if *b_type == BracketType::Opening)
     }
     pub fn is_closing_bracket(&self) -> bool {
-        match self {
-            TokenCategory::Bracket(b_type) => {
-                return *b_type == BracketType::Closing;
-            },
-            _ => false
-        }
+        matches!(self, TokenCategory::Bracket(b_type) if *b_type == BracketType::Closing)
     }
 }
 
 impl TokenKind {
     pub fn is_string(&self) -> bool {
-        match self {
-            TokenKind::String => true,
-            _ => false
-        }
+        matches!(self, TokenKind::String)
     }
     pub fn is_single_character(&self) -> bool {
-        match self {
-            TokenKind::SingleCharacter => true,
-            _ => false
-        }
+        matches!(self, TokenKind::SingleCharacter)
     }
     pub fn is_number(&self) -> bool {
-        match self {
-            TokenKind::Number => true,
-            _ => false
-        }
+        matches!(self, TokenKind::Number)
+    }
+    pub fn is_year(&self) -> bool {
+        matches!(self, TokenKind::Year)
     }
     pub fn is_number_like(&self) -> bool {
-        match self {
-            TokenKind::NumberLike => true,
-            _ => false
-        }
+        matches!(self, TokenKind::NumberLike)
     }
     pub fn is_number_or_like(&self) -> bool {
-        match self {
-            TokenKind::Number => true,
-            TokenKind::NumberLike => true,
-            _ => false
-        }
+        matches!(self, TokenKind::Number | TokenKind::NumberLike)
+    }
+    /// Any number, including a year that may also be an episode number, e.g. "Episode 1999"
+    pub fn is_numeric(&self) -> bool {
+        matches!(self, TokenKind::Number | TokenKind::Year)
+    }
+    pub fn is_numeric_or_like(&self) -> bool {
+        matches!(self, TokenKind::Number | TokenKind::NumberLike | TokenKind::Year)
     }
 }
\ No newline at end of file
@@ -1,8 +1,20 @@
 use nom::character::complete::{digit1, multispace0};
 use nom::combinator::recognize;
+use std::sync::LazyLock;
+
 use nom::sequence::tuple;
 use regex::Regex;
 
+use crate::video_resolution::VideoResolution;
+
+static SEASON_AND_EPISODE_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[Ss]?(\d+)([Eex])(\d+)(?:[Ee]\d+)*(?:[VvXx]\d{1,4}|')?$").unwrap());
+static ADDITIONAL_EPISODES_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[Ss]?\d+[Eex]\d+((?:[Ee]\d+)+)").unwrap());
+static NUMBER_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\d+").unwrap());
+static RELEASE_VERSION_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\d([Vv]\d{1,2}|')$").unwrap());
+static EPISODE_NUMBER_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(\d{1,4})([Vv]\d{1,2}|')$").unwrap());
+static ORDINAL_NUMBER_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(\d+)(?i:st|nd|rd|th)$").unwrap());
+
 pub fn is_digits(input: &str) -> bool {
     match recognize(tuple((digit1::<_, ()>, multispace0)))(input) {
         Ok((remaining, _matched)) => remaining.is_empty(),
@@ -11,8 +23,7 @@ pub fn is_digits(input: &str) -> bool {
 }
 
 pub fn extract_season_and_episode(input: &str) -> Option<(String, String, String)> {
-    let re = Regex::new(r"^[Ss]?(\d+)([Eex])(\d+\D?\d{1,4})?$").unwrap();
-    if let Some(captures) = re.captures(input) {
+    if let Some(captures) = SEASON_AND_EPISODE_REGEX.captures(input) {
         let season = captures.get(1).map_or("", |m| m.as_str()).to_string();
         let separator = captures.get(2).map_or("", |m| m.as_str()).to_string();
         let episode = captures.get(3).map_or("", |m| m.as_str()).to_string();
@@ -22,12 +33,45 @@ pub fn extract_season_and_episode(input: &str) -> Option<(String, String, String
     }
 }
 
+/// Extracts the episodes following the first one in multi-episode files.
+/// e.g. "S01E01E02" -> ["02"], "S01E01E02E03" -> ["02", "03"]
+pub fn extract_additional_episodes(input: &str) -> Vec<String> {
+    match ADDITIONAL_EPISODES_REGEX.captures(input) {
+        Some(captures) => NUMBER_REGEX.find_iter(&captures[1]).map(|m| m.as_str().to_string()).collect(),
+        None => vec![],
+    }
+}
+
+/// Extracts the version suffix of an episode number.
+/// e.g. "S01E01v2" -> "v2", "03E03'" -> "'"
+pub fn extract_release_version(input: &str) -> Option<String> {
+    let captures = RELEASE_VERSION_REGEX.captures(input)?;
+    Some(captures[1].to_string())
+}
+
+/// Get the number of a release version.
+/// A "'" marks a revised release, e.g. "04'" is the same as "04v2".
+/// e.g. "v2" -> 2, "V3" -> 3, "'" -> 2
+pub fn parse_release_version(input: &str) -> Option<u32> {
+    if input == "'" {
+        return Some(2);
+    }
+    input.strip_prefix(['v', 'V'])?.parse::<u32>().ok()
+}
+
+/// Splits an episode number from its version suffix.
+/// e.g. "04v2" -> ("04", "v2"), "04'" -> ("04", "'")
+pub fn split_episode_number(input: &str) -> Option<(String, String)> {
+    let captures = EPISODE_NUMBER_REGEX.captures(input)?;
+    Some((captures[1].to_string(), captures[2].to_string()))
+}
+
 pub fn is_number_like(input: &str) -> bool {
     let mut chars = input.chars();
 
     // Check if the first character is a digit
     if let Some(first_char) = chars.next() {
-        if !first_char.is_digit(10) {
+        if !first_char.is_ascii_digit() {
             return false;
         }
     } else {
@@ -45,7 +89,7 @@ pub fn is_number_like(input: &str) -> bool {
         if last_char == '\'' {
             return true;
         }
-        if !last_char.is_digit(10) {
+        if !last_char.is_ascii_digit() {
             return false;
         }
     } else {
@@ -54,23 +98,31 @@ pub fn is_number_like(input: &str) -> bool {
     }
 
     // Count non-digit characters in the remaining string
-    let non_digits = chars.filter(|c| !c.is_digit(10)).count();
+    let non_digits = chars.filter(|c| !c.is_ascii_digit()).count();
 
     non_digits == 1
 }
 
 pub fn is_ordinal_number(input: &str) -> bool {
-    return input.to_lowercase().ends_with("th") ||
-        input.to_lowercase().ends_with("st") ||
-        input.to_lowercase().ends_with("rd")
+    parse_ordinal_number(input).is_some()
+}
+
+/// e.g. "1st" -> 1, "2nd" -> 2, "21st" -> 21
+pub fn parse_ordinal_number(input: &str) -> Option<u32> {
+    ORDINAL_NUMBER_REGEX.captures(input)?[1].parse().ok()
 }
 
 pub fn is_number_or_like(input: &str) -> bool {
-    return is_number_like(input) || is_digits(input)
+    is_number_like(input) || is_digits(input)
+}
+
+/// e.g. "2019"
+pub fn is_year(input: &str) -> bool {
+    input.len() == 4 && is_digits(input) && input.parse::<u32>().is_ok_and(|year| (1900..=2100).contains(&year))
 }
 
 pub fn number_is_zero_padded(input: &str) -> bool {
-    if let Ok(_) = input.parse::<u32>() {
+    if input.parse::<u32>().is_ok() {
         return input.starts_with('0')
     }
     false
@@ -78,32 +130,6 @@ pub fn number_is_zero_padded(input: &str) -> bool {
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 
-#[derive(Debug, PartialEq)]
-enum VideoResolution {
-    Standard(u32, u32),
-    HD(u32),
-    _4K,
-}
-
-fn parse_resolution(input: &str) -> Option<VideoResolution> {
-    let standard_regex = Regex::new(r"^\s*(\d+)\s*x\s*(\d+)\s*$").unwrap();
-    let hd_regex = Regex::new(r"^\s*(\d+)\s*[pP]\s*$").unwrap();
-    let _4k_regex = Regex::new(r"^\s*4K\s*$").unwrap();
-
-    if let Some(captures) = standard_regex.captures(input) {
-        let width = captures[1].parse().ok()?;
-        let height = captures[2].parse().ok()?;
-        Some(VideoResolution::Standard(width, height))
-    } else if let Some(captures) = hd_regex.captures(input) {
-        let height = captures[1].parse().ok()?;
-        Some(VideoResolution::HD(height))
-    } else if _4k_regex.is_match(input) {
-        Some(VideoResolution::_4K)
-    } else {
-        None
-    }
-}
-
 pub fn is_crc32(s: &str) -> bool {
     s.len() == 8 && is_hexadecimal_string(s)
 }
@@ -113,7 +139,7 @@ pub fn is_hexadecimal_string(s: &str) -> bool {
 }
 
 pub fn is_video_resolution(input: &str) -> bool {
-    parse_resolution(input).is_some()
+    VideoResolution::parse(input).is_some()
 }
 
 #[cfg(test)]
@@ -131,6 +157,57 @@ mod tests {
         assert_eq!(extract_season_and_episode("10E05x2"), Some(("10".to_string(), "E".to_string(), "05".to_string())));
     }
 
+    #[test]
+    fn test_multiple_episodes() {
+        assert_eq!(extract_season_and_episode("S01E01E02"), Some(("01".to_string(), "E".to_string(), "01".to_string())));
+        assert_eq!(extract_additional_episodes("S01E01E02"), vec!["02".to_string()]);
+        assert_eq!(extract_additional_episodes("S01E01E02E03v2"), vec!["02".to_string(), "03".to_string()]);
+        assert!(extract_additional_episodes("S01E01").is_empty());
+        assert!(extract_additional_episodes("S01E01v2").is_empty());
+    }
+
+    #[test]
+    fn test_split_episode_number() {
+        assert_eq!(split_episode_number("04v2"), Some(("04".to_string(), "v2".to_string())));
+        assert_eq!(split_episode_number("04'"), Some(("04".to_string(), "'".to_string())));
+        assert_eq!(split_episode_number("04"), None);
+        assert_eq!(split_episode_number("1st"), None);
+        assert_eq!(split_episode_number("01x05"), None);
+    }
+
+    #[test]
+    fn test_is_year() {
+        assert!(is_year("2019"));
+        assert!(is_year("1999"));
+        assert!(!is_year("1080"));
+        assert!(!is_year("20190"));
+        assert!(!is_year("S2019"));
+    }
+
+    #[test]
+    fn test_ordinal_number() {
+        assert_eq!(parse_ordinal_number("1st"), Some(1));
+        assert_eq!(parse_ordinal_number("2nd"), Some(2));
+        assert_eq!(parse_ordinal_number("3RD"), Some(3));
+        assert_eq!(parse_ordinal_number("4th"), Some(4));
+        assert_eq!(parse_ordinal_number("21st"), Some(21));
+        assert_eq!(parse_ordinal_number("th"), None);
+        assert_eq!(parse_ordinal_number("Fourth"), None);
+        assert!(is_number_like("2nd"));
+    }
+
+    #[test]
+    fn test_release_version() {
+        assert_eq!(extract_release_version("S01E01v2"), Some("v2".to_string()));
+        assert_eq!(extract_release_version("03E03'"), Some("'".to_string()));
+        assert_eq!(extract_release_version("S01E01"), None);
+        assert_eq!(extract_release_version("10E05x2"), None);
+        assert_eq!(parse_release_version("v2"), Some(2));
+        assert_eq!(parse_release_version("V3"), Some(3));
+        assert_eq!(parse_release_version("'"), Some(2));
+        assert_eq!(parse_release_version("2"), None);
+    }
+
     #[test]
     fn test_invalid_episodes() {
         assert_eq!(extract_season_and_episode("05E02a"), None);
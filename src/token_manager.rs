@@ -1,18 +1,30 @@
 #![allow(dead_code)]
 
+use crate::confidence::Rule;
 use crate::keyword::{Keyword, KeywordCategory, KeywordKind, KeywordPriority};
 use crate::keyword_manager::KeywordManager;
 use crate::metadata::MetadataKind;
-use crate::token::{Token, TokenCategory, TokenKind};
-use crate::token_helper::{extract_season_and_episode, is_digits, is_number_like, is_number_or_like, is_ordinal_number};
+use crate::token::{set_consecutive_spans, Token, TokenCategory, TokenId, TokenKind};
+use crate::token_helper::{extract_additional_episodes, extract_release_version, extract_season_and_episode, is_digits, is_number_like, is_number_or_like, is_ordinal_number, is_year};
 use crate::tokenizer;
 use crate::utils::replace_case_insensitive;
-use uuid::Uuid;
 
 #[derive(Debug, Clone)]
 pub struct TokenManager {
-    pub tokens: Vec<Token>,
+    tokens: Vec<Token>,
     pub keyword_manager: KeywordManager,
+    // Lookup tables, rebuilt when tokens are split or fused
+    positions: Vec<Option<Position>>, // Indexed by token id, None once the token was replaced
+    non_delimiters: Vec<usize>, // Index of every token that isn't a delimiter
+}
+
+///
+/// Where a token is, with and without counting delimiters.
+///
+#[derive(Debug, Clone, Copy)]
+struct Position {
+    index: usize,
+    non_delimiter_index: Option<usize>,
 }
 
 ///
@@ -20,52 +32,137 @@ pub struct TokenManager {
 ///
 impl TokenManager {
     pub fn new(tokens: Vec<Token>) -> TokenManager {
+        TokenManager::new_with_keyword_manager(tokens, KeywordManager::new())
+    }
+
+    pub fn new_with_keyword_manager(tokens: Vec<Token>, keyword_manager: KeywordManager) -> TokenManager {
         let mut tm = TokenManager {
             tokens,
-            keyword_manager: KeywordManager::new(),
+            keyword_manager,
+            positions: vec![],
+            non_delimiters: vec![],
         };
 
+        for (index, token) in tm.tokens.iter_mut().enumerate() {
+            token.id = TokenId(index);
+        }
+        tm.reindex();
         tm.pre_processing();
 
         tm
     }
 
-    pub fn get_tokens(&mut self) -> Vec<Token> {
-        return self.tokens.clone();
+    pub fn tokens(&self) -> &[Token] {
+        &self.tokens
     }
 
-    pub fn update_token_category(&mut self, id: Uuid, token_category: TokenCategory) {
-        if let Some(token) = self.tokens.iter_mut().find(|t| t.uuid == id) {
-            token.category = token_category
-        }
+    pub fn get_token(&self, id: TokenId) -> Option<&Token> {
+        let position = self.positions.get(id.0).copied().flatten()?;
+        self.tokens.get(position.index)
     }
 
-    pub fn overwrite_token(&mut self, id: Uuid, new_token: Token) {
-        if let Some(token) = self.tokens.iter_mut().find(|t| t.uuid == id) {
-            *token = new_token
-        }
+    pub fn update_token_category(&mut self, id: TokenId, token_category: TokenCategory) {
+        self.update_token(id, |token| token.category = token_category);
+    }
+
+    ///
+    /// Marks the token as a metadata kind, along with the rule that decided it.
+    ///
+    pub fn assign_metadata_kind(&mut self, id: TokenId, kind: MetadataKind, rule: Rule) {
+        self.update_token(id, |token| {
+            token.category = TokenCategory::Known(kind);
+            token.rule = Some(rule);
+        });
+    }
+
+    ///
+    /// Replaces the token, the new token keeps its id.
+    ///
+    pub fn overwrite_token(&mut self, id: TokenId, new_token: Token) {
+        self.update_token(id, |token| *token = Token { id, ..new_token });
     }
 
     pub fn flatten_token_at(&mut self, index: usize, token_parts: Vec<Token>) {
         // Check if the index is within bounds
-        if index > self.tokens.len() {
-            return ();
+        if index >= self.tokens.len() {
+            return;
         }
 
-        // Remove the existing token at the specified index
-        self.tokens.remove(index);
+        // Replace the existing token at the specified index with the new tokens
+        let token_parts = token_parts.into_iter().map(|t| self.with_next_id(t)).collect::<Vec<Token>>();
+        self.tokens.splice(index..=index, token_parts);
+        self.reindex();
+    }
 
-        // Insert the new tokens at the specified index
-        for (i, token) in token_parts.into_iter().enumerate() {
-            self.tokens.insert(index + i, token);
+    /// Replace the tokens starting at the specified index with a single token.
+    /// e.g. "07", ".", "5" -> "07.5"
+    pub fn fuse_tokens_at(&mut self, index: usize, count: usize, new_token: Token) {
+        if index + count > self.tokens.len() {
+            return;
         }
+
+        let new_token = self.with_next_id(new_token);
+        self.tokens.splice(index..index + count, [new_token]);
+        self.reindex();
+    }
+
+    //------------ Identity -------------
+
+    ///
+    /// Ids are never reused, the next one is past every id given so far.
+    ///
+    fn with_next_id(&mut self, token: Token) -> Token {
+        let id = TokenId(self.positions.len());
+        self.positions.push(None);
+        Token { id, ..token }
+    }
+
+    fn update_token<F: FnOnce(&mut Token)>(&mut self, id: TokenId, update: F) {
+        let Some(position) = self.positions.get(id.0).copied().flatten() else {
+            return;
+        };
+        let token = &mut self.tokens[position.index];
+        let was_delimiter = token.category.is_delimiter();
+        update(token);
+        // Only needed if the token stops being skipped as a delimiter, or starts to
+        if token.category.is_delimiter() != was_delimiter {
+            self.reindex();
+        }
+    }
+
+    fn reindex(&mut self) {
+        let len = self.tokens.iter().map(|t| t.id.0 + 1).max().unwrap_or(0).max(self.positions.len());
+        self.positions = vec![None; len];
+        self.non_delimiters.clear();
+
+        for (index, token) in self.tokens.iter().enumerate() {
+            let non_delimiter_index = if token.category.is_delimiter() {
+                None
+            } else {
+                self.non_delimiters.push(index);
+                Some(self.non_delimiters.len() - 1)
+            };
+            self.positions[token.id.0] = Some(Position { index, non_delimiter_index });
+        }
+    }
+
+    fn get_token_at(&self, index: usize, skip_delimiter: bool) -> Option<&Token> {
+        if skip_delimiter {
+            self.non_delimiters.get(index).map(|i| &self.tokens[*i])
+        } else {
+            self.tokens.get(index)
+        }
+    }
+
+    fn get_tokens_from(&self, index: usize, skip_delimiter: bool) -> impl Iterator<Item = &Token> {
+        (index..).map_while(move |i| self.get_token_at(i, skip_delimiter))
     }
 
     ///
     /// Identifies whether the token is a keyword by comparing it to all keywords.
     /// It will prefer "combination" keywords over "standalone" ones.
     ///
-    pub fn identify_keyword(&mut self, token: &Token) -> Option<Vec<Token>> {
+    pub fn identify_keyword(&self, token: &Token) -> Option<Vec<Token>> {
 
         if token.category.is_known() {
             return None;
@@ -80,27 +177,28 @@ impl TokenManager {
             for keyword in iter {
                 // Validate the keyword
                 if let Some(ret) = self.validate_keyword(token, keyword) {
-                    match ret[0].clone().category {
-                        TokenCategory::Keyword(kw) => {
-                            match kw.kind {
-                                // If the validated keyword is "standalone", make sure we didn't
-                                // already validate another kind.
-                                KeywordKind::Standalone => {
-                                    if found == None {
-                                        found = Some(ret)
-                                    }
-                                }
-                                _ => {
+                    if let TokenCategory::Keyword(kw) = ret[0].clone().category {
+                        match kw.kind {
+                            // If the validated keyword is "standalone", make sure we didn't
+                            // already validate another kind.
+                            KeywordKind::Standalone => {
+                                if found.is_none() {
                                     found = Some(ret)
                                 }
                             }
+                            _ => {
+                                found = Some(ret)
+                            }
                         }
-                        _ => {}
                     };
                 }
             }
         }
-        return found;
+        // e.g. "S01E04" -> "S", "01", "E", "04"
+        if let Some(token_parts) = found.as_mut() {
+            set_consecutive_spans(token_parts, token.span);
+        }
+        found
     }
 
     ///
@@ -108,8 +206,8 @@ impl TokenManager {
     ///
     /// If the returned vector has more than one Token, the input token should be of TokenCategory::TokenParts(Vec<Token>).
     ///
-    fn validate_keyword(&mut self, token: &Token, keyword: &Keyword) -> Option<Vec<Token>> {
-        return match keyword.kind {
+    fn validate_keyword(&self, token: &Token, keyword: &Keyword) -> Option<Vec<Token>> {
+        match keyword.kind {
 
             // If the keyword is of combined type and the next token should be NumberLike
             // e.g: "S" or "E"
@@ -119,17 +217,41 @@ impl TokenManager {
                 if let Some(res) = extract_season_and_episode(token.value.as_str()) {
                     // Here is the only place where we will immediately employ TokenCategory::Known
                     // since we are sure
-                    return Some(vec![
+                    let mut token_parts = vec![
                         Token::new_with_kind(keyword.value.to_string(), TokenCategory::Keyword(keyword.clone()), TokenKind::String),
-                        Token::new_with_kind(res.0.as_str(), TokenCategory::Known(MetadataKind::Season), TokenKind::Number),
+                        Token::new_with_kind(res.0.as_str(), TokenCategory::Known(MetadataKind::Season), TokenKind::Number).with_rule(Rule::CombinedSeasonEpisode),
                         Token::new_with_kind(
                             res.1.as_str(),
                             TokenCategory::Keyword(
                                 Keyword::new(res.1.to_string(), KeywordCategory::EpisodePrefix, KeywordKind::Combined { next_token_kind: TokenKind::NumberLike }, KeywordPriority::Normal)
                             ),
                             TokenKind::SingleCharacter),
-                        Token::new_with_kind(res.2.as_str(), TokenCategory::Known(MetadataKind::EpisodeNumber), if is_digits(res.2.as_str()) { TokenKind::Number } else { TokenKind::NumberLike }),
-                    ]);
+                    ];
+                    // e.g. "S01E01E02" -> "01", "E", "02"
+                    let additional_episodes = extract_additional_episodes(token.value.as_str());
+                    let episodes = std::iter::once(res.2.clone()).chain(additional_episodes);
+                    for (i, episode) in episodes.enumerate() {
+                        if i > 0 {
+                            token_parts.push(Token::new_with_kind(
+                                "E",
+                                TokenCategory::Keyword(
+                                    Keyword::new("E".to_string(), KeywordCategory::EpisodePrefix, KeywordKind::Combined { next_token_kind: TokenKind::NumberLike }, KeywordPriority::Normal)
+                                ),
+                                TokenKind::SingleCharacter));
+                        }
+                        token_parts.push(Token::new_with_kind(episode.as_str(), TokenCategory::Known(MetadataKind::EpisodeNumber), TokenKind::Number).with_rule(Rule::CombinedSeasonEpisode));
+                    }
+                    // The last episode stays NumberLike if a version was split from it, e.g. "01v2"
+                    if let Some(last_episode) = token_parts.last_mut() {
+                        if !token.value.ends_with(last_episode.value.as_str()) {
+                            last_episode.kind = TokenKind::NumberLike;
+                        }
+                    }
+                    // e.g. "S01E01v2" -> "v2"
+                    if let Some(version) = extract_release_version(token.value.as_str()) {
+                        token_parts.push(Token::new_with_kind(version, TokenCategory::Known(MetadataKind::ReleaseVersion), TokenKind::String).with_rule(Rule::CombinedSeasonEpisode));
+                    }
+                    return Some(token_parts);
                 }
 
                 // Split and check adjacent token e.g. S01 -> S 01
@@ -142,7 +264,7 @@ impl TokenManager {
                 }
 
 
-                return None;
+                None
             }
 
             // If the keyword is of combined or separated type and the next token should be NumberLike
@@ -167,7 +289,7 @@ impl TokenManager {
                     ]);
                 }
 
-                return None;
+                None
             }
             KeywordKind::Separated { next_token_kind: TokenKind::NumberLike } => {
                 if self.next_token_is_number_like(token) {
@@ -176,7 +298,7 @@ impl TokenManager {
                     ]);
                 }
 
-                return None;
+                None
             }
             KeywordKind::OrdinalSuffix => {
 
@@ -186,78 +308,57 @@ impl TokenManager {
                     ]);
                 }
 
-                return None;
+                None
             }
             // If the keyword is standalone, we don't need to check adjacent tokens,
-            KeywordKind::Standalone => Some(vec![
-                Token::new_with_kind(token.value.clone(), TokenCategory::Keyword(keyword.clone()), TokenKind::String)
-            ]),
+            // but the whole token has to match, e.g. "Tsubasa" is not "TS"
+            KeywordKind::Standalone => {
+                if token.value.to_uppercase() != keyword.value {
+                    return None;
+                }
+                Some(vec![
+                    Token::new_with_kind(token.value.clone(), TokenCategory::Keyword(keyword.clone()), TokenKind::String)
+                ])
+            }
             _ => None
-        };
+        }
     }
 
     //--------------------
 
-    pub fn get_token_by_metadata_kind(&mut self, kind: MetadataKind) -> Option<Token> {
-        let mut m = self.tokens.iter();
-        m.find(|t| t.has_metadata_kind(kind)).cloned()
-    }
-
-    pub fn has_token_with_metadata_kind(&mut self, kind: MetadataKind) -> bool {
-        self.get_token_by_metadata_kind(kind).is_some()
-    }
-
-    pub fn get_index_of_token(&mut self, token: &Token, skip_delimiter: bool) -> Option<usize> {
-        let mut m = self.tokens.iter().filter(|t| if skip_delimiter { t.category != TokenCategory::Delimiter } else { true });
-        m.position(|t| t.uuid == token.uuid)
-    }
-
-    // pub fn get_known_token_after(&mut self, index: usize, category: TokenCategory, skip_delimiter: bool) -> Option<Token> {
-    //     let m = self.tokens.iter().filter(|t| if skip_delimiter { t.category != TokenCategory::Delimiter } else { true });
-    //     let mut iter = m.skip(index + 1);
-    //
-    //     iter.find(|t| t.category == category).cloned()
-    // }
-    //
-    // pub fn get_known_token_before(&mut self, index: usize, category: TokenCategory, skip_delimiter: bool) -> Option<Token> {
-    //     let m = self.tokens.iter().filter(|t| if skip_delimiter { t.category != TokenCategory::Delimiter } else { true });
-    //     let count = m.clone().count();
-    //     let mut iter = m.rev().skip(count - index);
-    //     iter.find(|t| t.category == category).cloned()
-    // }
-
-    pub fn get_token_after(&mut self, index: usize, skip_delimiter: bool) -> Option<Token> {
-        let m = self.tokens.iter().filter(|t| if skip_delimiter { t.category != TokenCategory::Delimiter } else { true });
-        let mut iter = m.skip(index + 1);
-        iter.next().cloned()
-    }
-
-    pub fn get_token_by_kind_after(&mut self, index: usize, token_kind: TokenKind, skip_delimiter: bool) -> Option<Token> {
-        let m = self.tokens.iter().filter(|t| if skip_delimiter { t.category != TokenCategory::Delimiter } else { true });
-        let mut iter = m.skip(index + 1);
-        match iter.next() {
-            None => None,
-            Some(token) => {
-                return if token.kind == token_kind {
-                    Some(token.clone())
-                } else {
-                    None
-                }
-            }
+    pub fn get_token_by_metadata_kind(&self, kind: MetadataKind) -> Option<Token> {
+        self.tokens.iter().find(|t| t.has_metadata_kind(kind)).cloned()
+    }
+
+    pub fn has_token_with_metadata_kind(&self, kind: MetadataKind) -> bool {
+        self.tokens.iter().any(|t| t.has_metadata_kind(kind))
+    }
+
+    pub fn get_index_of_token(&self, token: &Token, skip_delimiter: bool) -> Option<usize> {
+        let position = self.positions.get(token.id.0).copied().flatten()?;
+        if skip_delimiter {
+            position.non_delimiter_index
+        } else {
+            Some(position.index)
         }
     }
 
-    pub fn get_token_before(&mut self, index: usize, skip_delimiter: bool) -> Option<Token> {
-        let m = self.tokens.iter().filter(|t| if skip_delimiter { t.category != TokenCategory::Delimiter } else { true });
-        let count = m.clone().count();
-        let mut iter = m.rev().skip(count - index);
-        iter.next().cloned()
+    pub fn get_token_after(&self, index: usize, skip_delimiter: bool) -> Option<Token> {
+        self.get_token_at(index + 1, skip_delimiter).cloned()
     }
 
-    pub fn get_tokens_until_category(&mut self, index: usize, category: TokenCategory, skip_delimiter: bool) -> Option<Vec<Token>> {
-        let m = self.tokens.iter().filter(|t| if skip_delimiter { t.category != TokenCategory::Delimiter } else { true });
-        let iter = m.skip(index + 1);
-        let tokens = iter
+    pub fn get_token_by_kind_after(&self, index: usize, token_kind: TokenKind, skip_delimiter: bool) -> Option<Token> {
+        self.get_token_at(index + 1, skip_delimiter)
+            .filter(|t| t.kind == token_kind)
+            .cloned()
+    }
+
+    pub fn get_token_before(&self, index: usize, skip_delimiter: bool) -> Option<Token> {
+        self.get_token_at(index.checked_sub(1)?, skip_delimiter).cloned()
+    }
+
+    pub fn get_tokens_until_category(&self, index: usize, category: TokenCategory, skip_delimiter: bool) -> Option<Vec<Token>> {
+        let tokens = self.get_tokens_from(index + 1, skip_delimiter)
             .take_while(|t| t.category != category)
             .cloned()
             .collect::<Vec<Token>>();
@@ -269,17 +370,13 @@ impl TokenManager {
         }
     }
 
-    pub fn get_next_token_from(&mut self, index: usize, skip_delimiter: bool) -> Option<Token> {
-        let m = self.tokens.iter().filter(|t| if skip_delimiter { t.category != TokenCategory::Delimiter } else { true });
-        let mut iter = m.skip(index + 1);
-        iter.next().cloned()
+    pub fn get_next_token_from(&self, index: usize, skip_delimiter: bool) -> Option<Token> {
+        self.get_token_after(index, skip_delimiter)
     }
 
     // TODO redo
     pub fn get_matching_tokens_after(&self, index: usize, sequence: Vec<TokenCategory>, skip_delimiter: bool) -> Option<Vec<Token>> {
-        let m = self.tokens.iter().filter(|t| if skip_delimiter { t.category != TokenCategory::Delimiter } else { true });
-
-        let mut iter = m.skip(index + 1);
+        let mut iter = self.get_tokens_from(index + 1, skip_delimiter);
         let mut matching_tokens = Vec::new();
 
         for category in &sequence {
@@ -297,31 +394,27 @@ impl TokenManager {
     //------------ Private -------------
 
     ///
-    /// Checks if the following token (no delimiter) is of TokenKind::Number or TokenKind::NumberLike
+    /// Checks if the following token (no delimiter) is a number, e.g. of TokenKind::Number, TokenKind::NumberLike or TokenKind::Year
     ///
-    fn next_token_is_number_like(&mut self, token: &Token) -> bool {
+    fn next_token_is_number_like(&self, token: &Token) -> bool {
         // Check if the following token is NumberLike
-        return if let Some(token_idx) = self.get_index_of_token(&token, true) {
+        if let Some(token_idx) = self.get_index_of_token(token, true) {
             if let Some(next_token) = self.get_token_after(token_idx, true) {
-                match next_token.kind {
-                    TokenKind::Number => true,
-                    TokenKind::NumberLike => true,
-                    _ => false,
-                }
+                next_token.kind.is_numeric_or_like()
             } else {
                 false
             }
         } else {
             false
-        };
+        }
     }
 
     ///
     /// Checks if the preceding token (no delimiter) is of TokenKind::Number or TokenKind::NumberLike
     ///
-    fn previous_token_is_ordinal_number(&mut self, token: &Token) -> bool {
+    fn previous_token_is_ordinal_number(&self, token: &Token) -> bool {
         // Check if the following token is NumberLike
-        return if let Some(token_idx) = self.get_index_of_token(&token, true) {
+        if let Some(token_idx) = self.get_index_of_token(token, true) {
             if let Some(prev_token) = self.get_token_before(token_idx, true) {
                 match prev_token.kind {
                     TokenKind::NumberLike => {
@@ -337,7 +430,7 @@ impl TokenManager {
             }
         } else {
             false
-        };
+        }
     }
 
     /// Identify the kinds of each token.
@@ -345,7 +438,10 @@ impl TokenManager {
     fn pre_processing(&mut self) {
         let len = self.tokens.len();
         for i in 0..len {
-            if is_digits(self.tokens[i].value.as_str()) {
+            if is_year(self.tokens[i].value.as_str()) {
+                self.tokens[i].kind = TokenKind::Year;
+                //
+            } else if is_digits(self.tokens[i].value.as_str()) {
                 self.tokens[i].kind = TokenKind::Number;
                 //
             } else if is_number_like(self.tokens[i].value.as_str()) {
@@ -369,8 +465,6 @@ fn test_new_token_manager() {
     let tokens = tokenizer::tokenize(&input);
 
     let _token_manager = TokenManager::new(tokens.clone());
-
-    assert!(true);
 }
 
 #[test]
@@ -379,11 +473,11 @@ fn test_token_kind_number_like() {
 
     let tokens = tokenizer::tokenize(&input);
 
-    let mut token_manager = TokenManager::new(tokens.clone());
+    let token_manager = TokenManager::new(tokens.clone());
 
-    println!("{:#?}", token_manager.get_tokens());
+    println!("{:#?}", token_manager.tokens());
 
-    for token in token_manager.get_tokens() {
+    for token in token_manager.tokens() {
         if token.value != " " {
             assert_eq!(TokenKind::NumberLike, token.kind)
         }
@@ -413,6 +507,18 @@ fn test_identify_keyword() {
     test_identify_keyword_deeply_combined("S01x01v2", vec![TokenKind::Number, TokenKind::NumberLike]);
 }
 
+#[test]
+fn test_identify_keyword_release_version() {
+    let tokens = tokenizer::tokenize("S01E01v2");
+    let token_manager = TokenManager::new(tokens.clone());
+
+    let split_tokens = token_manager.identify_keyword(&token_manager.tokens()[0]).unwrap();
+
+    assert_eq!(5, split_tokens.len());
+    assert_eq!("v2", split_tokens[4].value);
+    assert!(split_tokens[4].has_metadata_kind(MetadataKind::ReleaseVersion));
+}
+
 #[test]
 fn test_identify_keyword_01() {
     test_identify_keyword_ordinal_suffix("4th season");
@@ -446,22 +552,22 @@ fn test_identify_keyword_panic_02() {
 
 fn test_identify_keyword_deeply_combined(input: &str, number_kinds: Vec<TokenKind>) {
     let tokens = tokenizer::tokenize(input);
-    let mut token_manager = TokenManager::new(tokens.clone());
+    let token_manager = TokenManager::new(tokens.clone());
 
-    let split_tokens = token_manager.identify_keyword(&tokens[0]).unwrap();
+    let split_tokens = token_manager.identify_keyword(&token_manager.tokens()[0]).unwrap();
 
     println!("----------------------------------------------------");
     println!("Input: {}", input);
     println!("Returned tokens: {:#?}", split_tokens);
 
     match split_tokens[0].category {
-        TokenCategory::Keyword(_) => assert!(true),
+        TokenCategory::Keyword(_) => {}
         _ => panic!("Expected Keyword, found {:?}", split_tokens[0].category)
     }
     assert_eq!(split_tokens[1].kind, number_kinds[0]);
 
     match split_tokens[2].category {
-        TokenCategory::Keyword(_) => assert!(true),
+        TokenCategory::Keyword(_) => {}
         _ => panic!("Expected Keyword, found {:?}", split_tokens[0].category)
     }
     assert_eq!(split_tokens[3].kind, number_kinds[1]);
@@ -469,9 +575,9 @@ fn test_identify_keyword_deeply_combined(input: &str, number_kinds: Vec<TokenKin
 
 fn test_identify_keyword_combined(input: &str, attached_token_expected_kind: TokenKind) {
     let tokens = tokenizer::tokenize(input);
-    let mut token_manager = TokenManager::new(tokens.clone());
+    let token_manager = TokenManager::new(tokens.clone());
 
-    for token in token_manager.get_tokens().iter().filter(|t| t.value != " ") {
+    for token in token_manager.tokens().iter().filter(|t| t.value != " ") {
         let split_tokens = token_manager.identify_keyword(token).unwrap();
 
         println!("----------------------------------------------------");
@@ -479,7 +585,7 @@ fn test_identify_keyword_combined(input: &str, attached_token_expected_kind: Tok
         println!("Returned tokens: {:#?}", split_tokens);
 
         match split_tokens[0].category {
-            TokenCategory::Keyword(_) => assert!(true),
+            TokenCategory::Keyword(_) => {}
             _ => panic!("Expected Keyword, found {:?}", split_tokens[0].category)
         }
         assert_eq!(split_tokens[1].kind, attached_token_expected_kind);
@@ -488,20 +594,20 @@ fn test_identify_keyword_combined(input: &str, attached_token_expected_kind: Tok
 
 fn test_identify_keyword_combined_or_separated(input: &str) {
     let tokens = tokenizer::tokenize(input);
-    let mut token_manager = TokenManager::new(tokens.clone());
+    let token_manager = TokenManager::new(tokens.clone());
 
     println!("----------------------------------------------------");
     println!("Input: {}", input);
     println!("Token: {:?}", &tokens[0]);
 
-    let split_tokens = token_manager.identify_keyword(&tokens[0]).unwrap();
+    let split_tokens = token_manager.identify_keyword(&token_manager.tokens()[0]).unwrap();
     println!("Returned tokens: {:#?}", split_tokens);
 
     match split_tokens[0].clone().category {
         TokenCategory::Keyword(keyword) => {
             match keyword.kind {
-                KeywordKind::CombinedOrSeparated { .. } => assert!(true),
-                KeywordKind::Separated { .. } => assert!(true),
+                KeywordKind::CombinedOrSeparated { .. } => {}
+                KeywordKind::Separated { .. } => {}
                 _ => panic!("Expected combined or separated keyword")
             }
         }
@@ -512,18 +618,18 @@ fn test_identify_keyword_combined_or_separated(input: &str) {
 
 fn test_identify_keyword_ordinal_suffix(input: &str) {
     let tokens = tokenizer::tokenize(input);
-    let mut token_manager = TokenManager::new(tokens.clone());
+    let token_manager = TokenManager::new(tokens.clone());
 
     println!("----------------------------------------------------");
     println!("Input: {}", input);
 
-    let ret = token_manager.identify_keyword(&tokens[2]).unwrap();
+    let ret = token_manager.identify_keyword(&token_manager.tokens()[2]).unwrap();
     println!("Returned tokens: {:#?}", ret);
 
     match ret[0].clone().category {
         TokenCategory::Keyword(keyword) => {
             match keyword.kind {
-                KeywordKind::OrdinalSuffix { .. } => assert!(true),
+                KeywordKind::OrdinalSuffix => {}
                 _ => panic!("Expected ordinal suffix keyword")
             }
         }
@@ -533,7 +639,7 @@ fn test_identify_keyword_ordinal_suffix(input: &str) {
 
 fn test_get_token_after(input: &str, index: usize, expected_value: &str) {
     let tokens = tokenizer::tokenize(input);
-    let mut token_manager = TokenManager::new(tokens.clone());
+    let token_manager = TokenManager::new(tokens.clone());
 
     let ret = token_manager.get_token_after(index, true);
     println!("{:#?}", ret.clone().unwrap());
@@ -542,9 +648,26 @@ fn test_get_token_after(input: &str, index: usize, expected_value: &str) {
 
 fn test_get_token_before(input: &str, index: usize, expected_value: &str) {
     let tokens = tokenizer::tokenize(input);
-    let mut token_manager = TokenManager::new(tokens.clone());
+    let token_manager = TokenManager::new(tokens.clone());
 
     let ret = token_manager.get_token_before(index, true);
     println!("{:#?}", ret.clone().unwrap());
     assert_eq!(expected_value, ret.unwrap().value);
-}
\ No newline at end of file
+}
+#[test]
+fn test_token_ids() {
+    let tokens = tokenizer::tokenize("Title - 07.5 [720p]");
+    let mut token_manager = TokenManager::new(tokens.clone());
+    assert_eq!((0..tokens.len()).map(TokenId).collect::<Vec<TokenId>>(), token_manager.tokens().iter().map(|t| t.id).collect::<Vec<TokenId>>());
+
+    // "07", ".", "5" -> "07.5"
+    let resolution = token_manager.tokens()[9].clone();
+    assert_eq!(Some(5), token_manager.get_index_of_token(&resolution, true));
+    token_manager.fuse_tokens_at(4, 3, Token::new("07.5", TokenCategory::Unknown));
+
+    assert_eq!(Some("07.5"), token_manager.get_token(TokenId(tokens.len())).map(|t| t.value.as_str()));
+    assert_eq!(None, token_manager.get_token(TokenId(4)));
+    assert_eq!(Some(7), token_manager.get_index_of_token(&resolution, false));
+    assert_eq!(Some(4), token_manager.get_index_of_token(&resolution, true));
+    assert_eq!(Some(String::from("07.5")), token_manager.get_token_before(3, true).map(|t| t.value));
+}
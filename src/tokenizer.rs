@@ -24,7 +24,9 @@
 
 use std::path::Path;
 
-use crate::token::{BracketType, Token, TokenCategory};
+use crate::keyword::KeywordCategory;
+use crate::keyword_manager::KeywordManager;
+use crate::token::{BracketType, Span, Token, TokenCategory};
 
 pub static DELIMITERS: [char; 5] = ['_', ' ', '　', '.', '|'];
 pub static SEPARATORS: [char; 3] = ['-', '+', '~'];
@@ -33,11 +35,17 @@ pub static CLOSING_BRACKETS: [char; 7] = [']', ')', '}', '\u{300D}', '\u{300F}',
 
 
 pub fn tokenize(input: &str) -> Vec<Token> {
-    let mut chars = input.chars();
+    tokenize_at(input, 0)
+}
 
+///
+/// Tokenizes the input, with spans starting at the offset.
+/// e.g. the offset of the file name in a path
+///
+pub fn tokenize_at(input: &str, offset: usize) -> Vec<Token> {
     let mut raw_tokens: Vec<Token> = vec![];
 
-    while let Some(char) = chars.next() {
+    for (index, char) in input.char_indices() {
         let category = match char {
             _ if OPENING_BRACKETS.contains(&char) => TokenCategory::Bracket(BracketType::Opening),
             _ if CLOSING_BRACKETS.contains(&char) => TokenCategory::Bracket(BracketType::Closing),
@@ -45,7 +53,8 @@ pub fn tokenize(input: &str) -> Vec<Token> {
             _ if SEPARATORS.contains(&char) => TokenCategory::Separator,
             _ => TokenCategory::Unknown
         };
-        raw_tokens.push(Token::new(&char, category))
+        let start = offset + index;
+        raw_tokens.push(Token::new(char, category).with_span(start, start + char.len_utf8()))
     }
 
     let mut tokens = fuse_unknown_tokens(raw_tokens);
@@ -55,7 +64,7 @@ pub fn tokenize(input: &str) -> Vec<Token> {
     tokens
 }
 
-fn find_enclosed_tokens(tokens: &mut Vec<Token>) {
+fn find_enclosed_tokens(tokens: &mut [Token]) {
     let mut opened = false;
     let mut enclosed_buffer: Vec<&mut Token> = vec![];
 
@@ -81,13 +90,9 @@ fn find_enclosed_tokens(tokens: &mut Vec<Token>) {
 
     // If it's still opened (last item is not the closing bracket)
     if let Some(last_item) = enclosed_buffer.last() {
-        match last_item.category {
-            TokenCategory::Unknown => {
-                if let Some(_) = enclosed_buffer.pop() {
-                    // last item removed
-                }
-            }
-            _ => {}
+        if last_item.category == TokenCategory::Unknown {
+            // last item removed
+            enclosed_buffer.pop();
         }
     }
 
@@ -102,6 +107,7 @@ fn find_enclosed_tokens(tokens: &mut Vec<Token>) {
 fn fuse_unknown_tokens(tokens: Vec<Token>) -> Vec<Token> {
     let mut fused_tokens = Vec::new();
     let mut unknown_buffer = String::new();
+    let mut unknown_span = Span::default();
     let mut is_unknown_sequence = false;
 
     for token in tokens {
@@ -110,16 +116,18 @@ fn fuse_unknown_tokens(tokens: Vec<Token>) -> Vec<Token> {
                 if is_unknown_sequence {
                     // Continue building the unknown sequence
                     unknown_buffer.push_str(&token.value);
+                    unknown_span = Span::join(unknown_span, token.span);
                 } else {
                     // Start a new unknown sequence
                     unknown_buffer = token.value.clone();
+                    unknown_span = token.span;
                     is_unknown_sequence = true;
                 }
             }
             _ => {
                 // End of the unknown sequence
                 if is_unknown_sequence {
-                    fused_tokens.push(Token::new(unknown_buffer.clone(), TokenCategory::Unknown));
+                    fused_tokens.push(Token::new(unknown_buffer.clone(), TokenCategory::Unknown).with_span(unknown_span.start, unknown_span.end));
                     is_unknown_sequence = false;
                 }
                 fused_tokens.push(token);
@@ -129,7 +137,7 @@ fn fuse_unknown_tokens(tokens: Vec<Token>) -> Vec<Token> {
 
     // Handle the case where the last token is unknown
     if is_unknown_sequence {
-        fused_tokens.push(Token::new(unknown_buffer, TokenCategory::Unknown));
+        fused_tokens.push(Token::new(unknown_buffer, TokenCategory::Unknown).with_span(unknown_span.start, unknown_span.end));
     }
 
     fused_tokens
@@ -142,7 +150,32 @@ pub fn remove_file_extension(input: &str) -> String {
             return str_stem.into();
         }
     }
-    return input.into();
+    input.into()
+}
+
+/// Byte offset of the file name in the input, e.g. 7 in "/Anime/Title - 01.mkv"
+pub fn file_name_offset(input: &str) -> usize {
+    Path::new(input)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .and_then(|n| input.rfind(n))
+        .unwrap_or(0)
+}
+
+/// Splits the file name from its extension.
+/// The extension is only removed if it is a known FileExtension keyword,
+/// so that names like "...DD.x265-EMBER" are left untouched.
+pub fn split_file_extension(input: &str, keyword_manager: &KeywordManager) -> (String, Option<String>) {
+    let path = Path::new(input);
+    let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or(input);
+
+    if let Some(extension) = path.extension().and_then(|e| e.to_str()) {
+        if keyword_manager.find_by_category(extension, KeywordCategory::FileExtension).is_some() {
+            let stem = &file_name[..file_name.len() - extension.len() - 1];
+            return (stem.to_string(), Some(extension.to_string()));
+        }
+    }
+    (file_name.to_string(), None)
 }
 
 //---------------
@@ -158,3 +191,37 @@ fn test_enclosed() {
     assert_eq!(String::from("HorribleSubs"), tokens[1].value);
     assert!(tokens[1].is_enclosed());
 }
+
+#[test]
+fn test_split_file_extension() {
+    let keyword_manager = KeywordManager::new();
+
+    assert_eq!(
+        (String::from("[HorribleSubs] Tower of Druaga - 04 [480p]"), Some(String::from("mkv"))),
+        split_file_extension("[HorribleSubs] Tower of Druaga - 04 [480p].mkv", &keyword_manager)
+    );
+    assert_eq!(
+        (String::from("Violet.Evergarden.The.Movie.1080p.DD.x265-EMBER"), None),
+        split_file_extension("Violet.Evergarden.The.Movie.1080p.DD.x265-EMBER", &keyword_manager)
+    );
+}
+
+#[test]
+fn test_spans() {
+    let input = "[Group] Título_01";
+    let tokens = tokenize_at(input, 3);
+
+    let spans: Vec<(String, usize, usize)> = tokens.iter().map(|t| (t.value.clone(), t.span.start, t.span.end)).collect();
+    assert_eq!(vec![
+        (String::from("["), 3, 4),
+        (String::from("Group"), 4, 9),
+        (String::from("]"), 9, 10),
+        (String::from(" "), 10, 11),
+        (String::from("Título"), 11, 18),
+        (String::from("_"), 18, 19),
+        (String::from("01"), 19, 21),
+    ], spans);
+
+    assert_eq!(7, file_name_offset("/Anime/Title - 01.mkv"));
+    assert_eq!(0, file_name_offset("Title - 01.mkv"));
+}
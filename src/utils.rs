@@ -1,6 +1,31 @@
-use regex::Regex;
-
 pub fn replace_case_insensitive(input: &str, pattern: &str, replacement: &str) -> String {
-    let regex_pattern = Regex::new(&format!(r"(?i){}", regex::escape(pattern))).unwrap();
-    regex_pattern.replace_all(input, replacement).to_string()
-}
\ No newline at end of file
+    if pattern.is_empty() {
+        return input.to_string();
+    }
+    let mut result = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(c) = rest.chars().next() {
+        match strip_prefix_case_insensitive(rest, pattern) {
+            Some(remaining) => {
+                result.push_str(replacement);
+                rest = remaining;
+            }
+            None => {
+                result.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    result
+}
+
+fn strip_prefix_case_insensitive<'a>(input: &'a str, prefix: &str) -> Option<&'a str> {
+    let mut chars = input.chars();
+    for p in prefix.chars() {
+        let c = chars.next()?;
+        if !c.to_lowercase().eq(p.to_lowercase()) {
+            return None;
+        }
+    }
+    Some(chars.as_str())
+}
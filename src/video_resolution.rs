@@ -0,0 +1,148 @@
+use std::fmt::{Display, Formatter};
+use std::sync::LazyLock;
+
+use regex::Regex;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ScanType {
+    Progressive, // e.g. 1080p
+    Interlaced, // e.g. 1080i
+}
+
+///
+/// Video resolution of a release, e.g. "1080p", "1080i", "1280x720", "4K".
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct VideoResolution {
+    pub width: Option<u32>,
+    pub height: u32,
+    pub scan_type: ScanType,
+}
+
+// (width, height)
+static STANDARD_RESOLUTIONS: [(u32, u32); 8] = [
+    (7680, 4320),
+    (3840, 2160),
+    (2560, 1440),
+    (1920, 1080),
+    (1280, 720),
+    (1024, 576),
+    (854, 480),
+    (640, 360),
+];
+
+// Compiled once, every token is checked for a resolution, e.g. "1280x720"
+static STANDARD_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\s*(\d{3,4})\s*[xX×]\s*(\d{3,4})\s*$").unwrap());
+// e.g. "1080p"
+static HEIGHT_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\s*(\d{3,4})\s*([pPiI])\s*$").unwrap());
+
+impl VideoResolution {
+    pub fn parse(input: &str) -> Option<VideoResolution> {
+        if let Some(captures) = STANDARD_REGEX.captures(input) {
+            return Some(VideoResolution {
+                width: Some(captures[1].parse().ok()?),
+                height: captures[2].parse().ok()?,
+                scan_type: ScanType::Progressive,
+            });
+        }
+
+        if let Some(captures) = HEIGHT_REGEX.captures(input) {
+            let scan_type = match &captures[2] {
+                "i" | "I" => ScanType::Interlaced,
+                _ => ScanType::Progressive,
+            };
+            return Some(VideoResolution {
+                width: None,
+                height: captures[1].parse().ok()?,
+                scan_type,
+            });
+        }
+
+        // Aliases
+        let (width, height) = match input.trim().to_uppercase().as_str() {
+            "8K" => (7680, 4320),
+            "4K" | "UHD" => (3840, 2160),
+            "QHD" => (2560, 1440),
+            "FHD" => (1920, 1080),
+            _ => return None,
+        };
+        Some(VideoResolution {
+            width: Some(width),
+            height,
+            scan_type: ScanType::Progressive,
+        })
+    }
+
+    ///
+    /// Height of the closest standard resolution, used to compare releases.
+    /// e.g. "1920x800" (cropped) -> 1080, "1280x720" -> 720, "1080i" -> 1080
+    ///
+    pub fn normalized_height(&self) -> u32 {
+        STANDARD_RESOLUTIONS
+            .iter()
+            .find(|(width, height)| {
+                // Allow some leeway for cropped or anamorphic videos
+                self.height * 10 >= height * 9 || self.width.is_some_and(|w| w * 10 >= width * 9)
+            })
+            .map(|(_, height)| *height)
+            .unwrap_or(self.height)
+    }
+
+    pub fn is_interlaced(&self) -> bool {
+        self.scan_type == ScanType::Interlaced
+    }
+}
+
+impl Display for VideoResolution {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match (self.width, self.scan_type) {
+            (Some(width), _) => write!(f, "{}x{}", width, self.height),
+            (None, ScanType::Progressive) => write!(f, "{}p", self.height),
+            (None, ScanType::Interlaced) => write!(f, "{}i", self.height),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(VideoResolution::parse("1080p"), Some(VideoResolution { width: None, height: 1080, scan_type: ScanType::Progressive }));
+        assert_eq!(VideoResolution::parse("1080i"), Some(VideoResolution { width: None, height: 1080, scan_type: ScanType::Interlaced }));
+        assert_eq!(VideoResolution::parse("1280x720"), Some(VideoResolution { width: Some(1280), height: 720, scan_type: ScanType::Progressive }));
+        assert_eq!(VideoResolution::parse("2160p").map(|r| r.normalized_height()), Some(2160));
+        assert_eq!(VideoResolution::parse("4K").map(|r| r.normalized_height()), Some(2160));
+        assert_eq!(VideoResolution::parse("UHD").map(|r| r.normalized_height()), Some(2160));
+        assert_eq!(VideoResolution::parse("FHD").map(|r| r.normalized_height()), Some(1080));
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert_eq!(VideoResolution::parse("1p"), None);
+        assert_eq!(VideoResolution::parse("x265"), None);
+        assert_eq!(VideoResolution::parse("HD"), None);
+    }
+
+    #[test]
+    fn test_normalized_height() {
+        assert_eq!(VideoResolution::parse("1920x800").unwrap().normalized_height(), 1080);
+        assert_eq!(VideoResolution::parse("1440x1080").unwrap().normalized_height(), 1080);
+        assert_eq!(VideoResolution::parse("720x480").unwrap().normalized_height(), 480);
+        assert_eq!(VideoResolution::parse("1080i").unwrap().normalized_height(), 1080);
+        assert_eq!(VideoResolution::parse("240p").unwrap().normalized_height(), 240);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!("1080p", VideoResolution::parse("1080p").unwrap().to_string());
+        assert_eq!("1080i", VideoResolution::parse("1080I").unwrap().to_string());
+        assert_eq!("1280x720", VideoResolution::parse("1280x720").unwrap().to_string());
+    }
+}